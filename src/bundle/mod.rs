@@ -0,0 +1,234 @@
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use common::Attribute;
+use definition::Definitions;
+use resolver::{ResolveError, Resolver};
+use swagger::Swagger;
+use validator::reference::Location;
+
+struct Bundler<'a> {
+    resolver: &'a Resolver,
+    root: PathBuf,
+    names: HashMap<(PathBuf, String), String>,
+    taken: HashSet<String>,
+    definitions: Definitions,
+}
+
+impl<'a> Bundler<'a> {
+    fn rewrite(&mut self, attribute: &mut Attribute) -> Result<(), ResolveError> {
+        if let Some(reference) = &attribute.reference {
+            let location = Location::from(reference.to_string());
+            if let Location::Remote(_, _) = location {
+                let name = self.import(&location)?;
+                attribute.reference = Some(format!("#/definitions/{}", name));
+            }
+        }
+        for child in attribute.children_mut() {
+            self.rewrite(child)?;
+        }
+        Ok(())
+    }
+
+    fn import(&mut self, location: &Location) -> Result<String, ResolveError> {
+        let (path, pointer, mut attribute) = self.resolver.fetch(location)?;
+        if path == self.root {
+            if let Some(name) = pointer.strip_prefix("/definitions/") {
+                if !name.contains('/') {
+                    return Ok(name.to_string());
+                }
+            }
+        }
+        let key = (path, pointer);
+        if let Some(name) = self.names.get(&key) {
+            return Ok(name.clone());
+        }
+        let name = self.allocate(&key.0, &key.1);
+        // Registered before descending, so a cycle through this schema
+        // comes back to the same name instead of importing it again.
+        self.names.insert(key, name.clone());
+        self.rewrite(&mut attribute)?;
+        self.definitions.insert(name.clone(), attribute);
+        Ok(name)
+    }
+
+    fn allocate(&mut self, path: &Path, pointer: &str) -> String {
+        let base = match pointer.rsplit('/').next() {
+            Some(token) if !token.is_empty() => token.replace("~1", "/").replace("~0", "~"),
+            _ => path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+        };
+        let mut name = base.clone();
+        let mut suffix = 1;
+        while self.taken.contains(&name) {
+            suffix += 1;
+            name = format!("{}{}", base, suffix);
+        }
+        self.taken.insert(name.clone());
+        name
+    }
+}
+
+/// Returns a copy of `swagger` with every schema referenced from another
+/// file pulled into its local `definitions`, so that all `$ref`s point
+/// inside the document itself.
+pub fn bundle(swagger: &Swagger, resolver: &Resolver) -> Result<Swagger, ResolveError> {
+    let mut swagger = swagger.clone();
+    let taken = match &swagger.definitions {
+        Some(definitions) => definitions.keys().cloned().collect(),
+        None => HashSet::new(),
+    };
+    let mut bundler = Bundler {
+        resolver,
+        root: resolver.root()?,
+        names: HashMap::new(),
+        taken,
+        definitions: Definitions::new(),
+    };
+    for schema in swagger.schemas_mut() {
+        bundler.rewrite(schema)?;
+    }
+    if !bundler.definitions.is_empty() {
+        let definitions = swagger.definitions.get_or_insert_with(Definitions::new);
+        definitions.extend(bundler.definitions);
+    }
+    Ok(swagger)
+}
+
+fn inline(
+    attribute: &mut Attribute, definitions: &Definitions, stack: &mut Vec<String>,
+) -> Result<(), ResolveError> {
+    let location = attribute
+        .reference
+        .as_ref()
+        .map(|r| Location::from(r.to_string()));
+    if let Some(Location::Local(name)) = location {
+        if stack.contains(&name) {
+            return Ok(());
+        }
+        *attribute = match definitions.get(&name) {
+            Some(definition) => definition.clone(),
+            None => {
+                return Err(ResolveError::Pointer(
+                    String::new(),
+                    format!("/definitions/{}", name),
+                ))
+            }
+        };
+        stack.push(name);
+        let result = inline(attribute, definitions, stack);
+        stack.pop();
+        return result;
+    }
+    for child in attribute.children_mut() {
+        inline(child, definitions, stack)?;
+    }
+    Ok(())
+}
+
+/// Returns a copy of `swagger` with every `$ref` replaced by its target.
+///
+/// A reference back into a schema that is already being expanded is kept
+/// as is, so recursive definitions stay finite; the definitions they point
+/// at are kept in the output for that reason.
+pub fn dereference(swagger: &Swagger, resolver: &Resolver) -> Result<Swagger, ResolveError> {
+    let mut swagger = bundle(swagger, resolver)?;
+    swagger.resolve_parameters()?;
+    swagger.resolve_responses()?;
+    let mut output = swagger.definitions.take();
+    let definitions = output.clone().unwrap_or_default();
+    for (name, definition) in output.iter_mut().flat_map(|d| d.iter_mut()) {
+        inline(definition, &definitions, &mut vec![name.clone()])?;
+    }
+    for schema in swagger.schemas_mut() {
+        inline(schema, &definitions, &mut Vec::new())?;
+    }
+    swagger.definitions = output;
+    Ok(swagger)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::{bundle, dereference};
+    use common::{Attribute, Referable, StringType, TypeDefinition};
+    use resolver::Resolver;
+    use swagger::Swagger;
+
+    fn load() -> (Swagger, Resolver) {
+        let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        path.push("src/bundle/test.yaml");
+        (Swagger::from_path(&path).unwrap(), Resolver::new(path))
+    }
+
+    fn property<'a>(attribute: &'a Attribute, name: &str) -> &'a Attribute {
+        match attribute.definition.as_ref().unwrap() {
+            TypeDefinition::Object(object) => object.properties.get(name).unwrap(),
+            _ => panic!("Not matched"),
+        }
+    }
+
+    #[test]
+    fn test_bundle() {
+        let (swagger, resolver) = load();
+        let bundled = bundle(&swagger, &resolver).unwrap();
+        let definitions = bundled.definitions.as_ref().unwrap();
+        let mut names: Vec<&String> = definitions.keys().collect();
+        names.sort();
+        assert_eq!(names, vec!["Error", "Error2", "Node", "Pet"]);
+
+        let pet = definitions.get("Pet").unwrap();
+        let node = property(pet, "node").reference.as_ref().unwrap();
+        assert_eq!(node, "#/definitions/Node");
+        let error = property(definitions.get("Node").unwrap(), "error");
+        assert_eq!(error.reference.as_ref().unwrap(), "#/definitions/Error2");
+        let owner = property(definitions.get("Error2").unwrap(), "owner");
+        assert_eq!(owner.reference.as_ref().unwrap(), "#/definitions/Pet");
+        assert_eq!(
+            pet.extensions.get("x-internal"),
+            Some(&serde_yaml::Value::Bool(true))
+        );
+
+        let yaml = serde_yaml::to_string(&bundled).unwrap();
+        let reloaded: Swagger = serde_yaml::from_str(&yaml).unwrap();
+        assert_eq!(reloaded, bundled);
+    }
+
+    #[test]
+    fn test_dereference() {
+        let (swagger, resolver) = load();
+        let inlined = dereference(&swagger, &resolver).unwrap();
+        let paths = inlined.paths.as_ref().unwrap();
+        let operation = paths.get("/pet").unwrap().post.as_ref().unwrap();
+        let body = operation.inline_parameters().next().unwrap();
+        let pet = body.schema.as_ref().unwrap();
+        assert!(pet.reference.is_none());
+
+        let node = property(pet, "node");
+        let children = match property(node, "children").definition.as_ref().unwrap() {
+            TypeDefinition::Array { items } => items,
+            _ => panic!("Not matched"),
+        };
+        assert_eq!(children.reference.as_ref().unwrap(), "#/definitions/Node");
+        let owner = property(property(node, "error"), "owner");
+        assert_eq!(owner.reference.as_ref().unwrap(), "#/definitions/Pet");
+
+        match operation.response(404).unwrap() {
+            Referable::Inline(response) => {
+                let schema = response.schema.as_ref().unwrap();
+                assert!(schema.reference.is_none());
+                assert_eq!(
+                    schema.definition,
+                    Some(TypeDefinition::String(StringType {
+                        format: None,
+                        choices: None,
+                    }))
+                );
+            }
+            _ => panic!("Not matched"),
+        }
+    }
+}
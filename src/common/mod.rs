@@ -1,26 +1,104 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::btree_map;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::marker::PhantomData;
 
-#[derive(Debug, PartialEq, Deserialize)]
+use serde::de::{DeserializeOwned, Error, MapAccess, Visitor};
+use serde::{Deserialize, Deserializer};
+use serde_yaml::Value;
+
+use resolver::ResolveError;
+
+/// Keys the model does not interpret, kept so a document can be written
+/// back out without losing them. Vendor extensions are kept apart in
+/// `Extensions` wherever the model carries them.
+pub type Extra = BTreeMap<String, Value>;
+
+pub(crate) fn is_false(value: &bool) -> bool {
+    !*value
+}
+
+pub(crate) fn unmodeled<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Extra, D::Error> {
+    let mut extra = Extra::deserialize(deserializer)?;
+    extra.retain(|key, _| !key.starts_with("x-"));
+    Ok(extra)
+}
+
+/// Vendor extensions, the `x-` prefixed keys of an object.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct Extensions(BTreeMap<String, Value>);
+
+impl<'de> Deserialize<'de> for Extensions {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let mut map = BTreeMap::<String, Value>::deserialize(deserializer)?;
+        map.retain(|key, _| key.starts_with("x-"));
+        Ok(Extensions(map))
+    }
+}
+
+impl Extensions {
+    pub fn get(&self, name: &str) -> Option<&Value> {
+        self.0.get(name)
+    }
+
+    /// Decodes the extension `name` into `T`, e.g. `x-rate-limit` as `u32`.
+    pub fn get_as<T: DeserializeOwned>(&self, name: &str) -> Option<Result<T, serde_yaml::Error>> {
+        self.0
+            .get(name)
+            .map(|value| serde_yaml::from_value(value.clone()))
+    }
+
+    /// Adds or replaces an extension, returning the previous value. Names
+    /// without the `x-` prefix are not extensions and are rejected.
+    pub fn insert(&mut self, name: String, value: Value) -> Result<Option<Value>, String> {
+        if !name.starts_with("x-") {
+            return Err(format!("Extension {} must start with x-", name));
+        }
+        Ok(self.0.insert(name, value))
+    }
+
+    pub fn remove(&mut self, name: &str) -> Option<Value> {
+        self.0.remove(name)
+    }
+
+    pub fn iter(&self) -> btree_map::Iter<'_, String, Value> {
+        self.0.iter()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct IntegerType {
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub format: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub minimum: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub maximum: Option<i64>,
+    #[serde(rename = "enum", skip_serializing_if = "Option::is_none")]
+    pub choices: Option<Vec<i64>>,
 }
 
-#[derive(Debug, PartialEq, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct StringType {
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub format: Option<String>,
-    #[serde(rename = "enum")]
-    pub choices: Option<HashSet<String>>,
+    #[serde(rename = "enum", skip_serializing_if = "Option::is_none")]
+    pub choices: Option<Vec<String>>,
 }
 
-#[derive(Debug, PartialEq, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ObjectType {
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
     pub properties: HashMap<String, Attribute>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub required: Option<Vec<String>>,
 }
 
-#[derive(Debug, PartialEq, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 #[serde(tag = "type")]
 pub enum TypeDefinition {
@@ -32,16 +110,251 @@ pub enum TypeDefinition {
     },
     Object(ObjectType),
     File,
-    #[serde(skip_deserializing)]
+    #[serde(skip)]
     #[allow(dead_code)]
     Undefined,
 }
 
-#[derive(Debug, PartialEq, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct Attribute {
-    #[serde(default = "TypeDefinition::Undefined", flatten)]
+    #[serde(flatten, deserialize_with = "definition")]
     pub definition: Option<TypeDefinition>,
-    #[serde(rename = "$ref")]
+    #[serde(rename = "$ref", skip_serializing_if = "Option::is_none")]
     pub reference: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub description: Option<String>,
+    /// OpenAPI 3.0 only: `null` is accepted besides values of the type.
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub nullable: bool,
+    #[serde(rename = "oneOf", skip_serializing_if = "Option::is_none")]
+    pub one_of: Option<Vec<Attribute>>,
+    #[serde(rename = "anyOf", skip_serializing_if = "Option::is_none")]
+    pub any_of: Option<Vec<Attribute>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub not: Option<Box<Attribute>>,
+    #[serde(flatten)]
+    pub extensions: Extensions,
+    #[serde(flatten, deserialize_with = "schema_extra")]
+    pub extra: Extra,
+}
+
+impl Attribute {
+    /// Schemas nested directly inside this one.
+    pub fn children_mut(&mut self) -> Vec<&mut Attribute> {
+        let mut children: Vec<&mut Attribute> = match &mut self.definition {
+            Some(TypeDefinition::Array { items }) => vec![items],
+            Some(TypeDefinition::Object(object)) => object.properties.values_mut().collect(),
+            _ => Vec::new(),
+        };
+        children.extend(self.one_of.iter_mut().flatten());
+        children.extend(self.any_of.iter_mut().flatten());
+        children.extend(self.not.as_mut().map(|not| not.as_mut()));
+        children
+    }
+
+    fn branches(
+        &self, keyword: &Option<Vec<Attribute>>, extension: &str,
+    ) -> Option<Result<Vec<Attribute>, serde_yaml::Error>> {
+        match keyword {
+            Some(branches) => Some(Ok(branches.clone())),
+            None => self.extensions.get_as(extension),
+        }
+    }
+
+    /// Branches of `oneOf`, or of `x-oneOf` which Swagger 2.0 documents use
+    /// in its place.
+    pub fn one_of_branches(&self) -> Option<Result<Vec<Attribute>, serde_yaml::Error>> {
+        self.branches(&self.one_of, "x-oneOf")
+    }
+
+    /// Branches of `anyOf`, or of `x-anyOf`.
+    pub fn any_of_branches(&self) -> Option<Result<Vec<Attribute>, serde_yaml::Error>> {
+        self.branches(&self.any_of, "x-anyOf")
+    }
+
+    /// The schema of `not`, or of `x-not`.
+    pub fn not_branch(&self) -> Option<Result<Attribute, serde_yaml::Error>> {
+        match &self.not {
+            Some(not) => Some(Ok(not.as_ref().clone())),
+            None => self.extensions.get_as("x-not"),
+        }
+    }
+}
+
+/// The keywords of an `array` schema.
+#[derive(Deserialize)]
+struct ArrayType {
+    items: Box<Attribute>,
+}
+
+/// Decodes `value`, naming the keyword at fault in the error, e.g.
+/// `minimum: invalid type: string "abc", expected i64`.
+fn keywords<T: DeserializeOwned>(value: Value) -> Result<T, String> {
+    serde_path_to_error::deserialize(value).map_err(|e| format!("{}: {}", e.path(), e.inner()))
+}
+
+/// The `TypeDefinition` of a schema with the keywords `schema`, none when
+/// its `type` is absent or not modeled.
+fn type_definition(schema: &Extra) -> Result<Option<TypeDefinition>, String> {
+    let name = match schema.get("type") {
+        Some(Value::String(name)) => name.as_str(),
+        _ => return Ok(None),
+    };
+    let mapping = schema
+        .iter()
+        .map(|(k, v)| (Value::String(k.clone()), v.clone()));
+    let value = Value::Mapping(mapping.collect());
+    let definition = match name {
+        "boolean" => TypeDefinition::Boolean,
+        "integer" => TypeDefinition::Integer(keywords(value)?),
+        "string" => TypeDefinition::String(keywords(value)?),
+        "array" => TypeDefinition::Array {
+            items: keywords::<ArrayType>(value)?.items,
+        },
+        "object" => TypeDefinition::Object(keywords(value)?),
+        "file" => TypeDefinition::File,
+        _ => return Ok(None),
+    };
+    Ok(Some(definition))
+}
+
+/// Reports malformed type keywords, such as `minimum: abc`, rather than
+/// leaving the schema without a type.
+fn definition<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<TypeDefinition>, D::Error> {
+    let schema = Extra::deserialize(deserializer)?;
+    type_definition(&schema).map_err(D::Error::custom)
+}
+
+/// Keeps every keyword the `TypeDefinition` of the schema does not hold,
+/// so that no keyword is lost when the schema is written back.
+fn schema_extra<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Extra, D::Error> {
+    let mut extra = unmodeled(deserializer)?;
+    if let Ok(Some(definition)) = type_definition(&extra) {
+        let modeled = serde_yaml::to_value(&definition).map_err(D::Error::custom)?;
+        for key in modeled.as_mapping().into_iter().flat_map(|m| m.iter()) {
+            if let Some(key) = key.0.as_str() {
+                extra.remove(key);
+            }
+        }
+    }
+    Ok(extra)
+}
+
+/// An entry that is either written inline or refers elsewhere via `$ref`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum Referable<T> {
+    Reference {
+        #[serde(rename = "$ref")]
+        reference: String,
+    },
+    Inline(T),
+}
+
+/// Any mapping with a `$ref` is a reference. Everything else must be `T`,
+/// and the error of `T` is reported as is.
+impl<'de, T: DeserializeOwned> Deserialize<'de> for Referable<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = Value::deserialize(deserializer)?;
+        let key = Value::String("$ref".to_string());
+        match value.as_mapping().and_then(|mapping| mapping.get(&key)) {
+            Some(Value::String(reference)) => Ok(Referable::Reference {
+                reference: reference.clone(),
+            }),
+            Some(_) => Err(D::Error::custom("$ref is not a string")),
+            None => serde_yaml::from_value(value)
+                .map(Referable::Inline)
+                .map_err(D::Error::custom),
+        }
+    }
+}
+
+/// Replaces every reference among `entries` with a copy of what `lookup`
+/// finds for it, stopping at the first that cannot be found.
+pub(crate) fn resolve_references<'a, 'b, T, I, F>(entries: I, lookup: F) -> Result<(), ResolveError>
+where
+    T: Clone + 'a + 'b,
+    I: IntoIterator<Item = &'b mut Referable<T>>,
+    F: Fn(&str) -> Result<&'a T, ResolveError>,
+{
+    for entry in entries {
+        let resolved = match entry {
+            Referable::Reference { reference } => lookup(reference)?.clone(),
+            Referable::Inline(_) => continue,
+        };
+        *entry = Referable::Inline(resolved);
+    }
+    Ok(())
+}
+
+/// Responses keyed by HTTP status code, or `default`, next to the vendor
+/// extensions written among them.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Responses<T> {
+    #[serde(flatten)]
+    pub statuses: BTreeMap<String, Referable<T>>,
+    #[serde(flatten)]
+    pub extensions: Extensions,
+}
+
+impl<T> Responses<T> {
+    pub fn new(statuses: BTreeMap<String, Referable<T>>, extensions: Extensions) -> Self {
+        Responses {
+            statuses,
+            extensions,
+        }
+    }
+
+    pub fn get(&self, status: &str) -> Option<&Referable<T>> {
+        self.statuses.get(status)
+    }
+
+    pub fn iter(&self) -> btree_map::Iter<'_, String, Referable<T>> {
+        self.statuses.iter()
+    }
+
+    pub fn values_mut(&mut self) -> btree_map::ValuesMut<'_, String, Referable<T>> {
+        self.statuses.values_mut()
+    }
+}
+
+impl<'r, T> IntoIterator for &'r Responses<T> {
+    type Item = (&'r String, &'r Referable<T>);
+    type IntoIter = btree_map::Iter<'r, String, Referable<T>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'de, T: DeserializeOwned> Deserialize<'de> for Responses<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_map(ResponsesVisitor(PhantomData))
+    }
+}
+
+struct ResponsesVisitor<T>(PhantomData<T>);
+
+impl<'de, T: DeserializeOwned> Visitor<'de> for ResponsesVisitor<T> {
+    type Value = Responses<T>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a map of responses")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let mut responses = Responses::new(BTreeMap::new(), Extensions::default());
+        while let Some(key) = map.next_key::<String>()? {
+            if key.starts_with("x-") {
+                let value = map.next_value()?;
+                responses.extensions.0.insert(key, value);
+            } else {
+                let value = map.next_value()?;
+                responses.statuses.insert(key, value);
+            }
+        }
+        Ok(responses)
+    }
 }
@@ -1,12 +1,92 @@
-use common::Attribute;
-use std::collections::HashMap;
+use common::{Attribute, TypeDefinition};
+use std::collections::{HashMap, HashSet};
+use validator::reference::Location;
 
 pub type Definitions = HashMap<String, Attribute>;
 
+/// Follows the `$ref` of `attribute` through `definitions` to the schema it
+/// ends at. A reference that points at nothing, or back into the chain, is
+/// where the search stops.
+pub fn resolve<'a>(attribute: &'a Attribute, definitions: &'a Definitions) -> &'a Attribute {
+    let mut visited: HashSet<&str> = HashSet::new();
+    let mut attribute = attribute;
+    while let Some(reference) = &attribute.reference {
+        let name = match Location::from(reference.to_string()) {
+            Location::Local(name) | Location::Component(name) => name,
+            _ => break,
+        };
+        match definitions.get_key_value(&name) {
+            Some((name, target)) if visited.insert(name) => attribute = target,
+            _ => break,
+        }
+    }
+    attribute
+}
+
+/// Returns the names of definitions that no finite document can satisfy,
+/// such as an object requiring a property that refers back to itself with
+/// no base case. Optional properties and arrays always terminate a cycle.
+pub fn unsatisfiable(definitions: &Definitions) -> Vec<&str> {
+    let mut satisfiable: HashSet<&str> = HashSet::new();
+    loop {
+        let mut changed = false;
+        for (name, attribute) in definitions.iter() {
+            if !satisfiable.contains(name.as_str()) && is_satisfiable(attribute, &satisfiable) {
+                satisfiable.insert(name);
+                changed = true;
+            }
+        }
+        if !changed {
+            break;
+        }
+    }
+    let mut names: Vec<&str> = definitions
+        .keys()
+        .map(String::as_str)
+        .filter(|name| !satisfiable.contains(name))
+        .collect();
+    names.sort_unstable();
+    names
+}
+
+fn is_satisfiable(attribute: &Attribute, satisfiable: &HashSet<&str>) -> bool {
+    if let Some(reference) = &attribute.reference {
+        return match Location::from(reference.to_string()) {
+            Location::Local(name) | Location::Component(name) => {
+                satisfiable.contains(name.as_str())
+            }
+            _ => true,
+        };
+    }
+    // A value has to match some branch of `oneOf` and of `anyOf`.
+    let composed = vec![attribute.one_of_branches(), attribute.any_of_branches()];
+    for branches in composed.into_iter().flatten().flatten() {
+        if !branches
+            .iter()
+            .any(|branch| is_satisfiable(branch, satisfiable))
+        {
+            return false;
+        }
+    }
+    match &attribute.definition {
+        Some(TypeDefinition::Object(object)) => {
+            object
+                .required
+                .iter()
+                .flatten()
+                .all(|name| match object.properties.get(name) {
+                    Some(property) => is_satisfiable(property, satisfiable),
+                    None => true,
+                })
+        }
+        _ => true,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use common::TypeDefinition;
-    use definition::Definitions;
+    use definition::{unsatisfiable, Definitions};
     use std::collections::HashMap;
     use std::fs::File;
     use std::path::PathBuf;
@@ -40,4 +120,37 @@ mod tests {
             _ => panic!("Not matched"),
         }
     }
+
+    #[test]
+    fn test_unsatisfiable() {
+        let schema = "
+        TreeNode:
+          type: object
+          required:
+            - name
+          properties:
+            name:
+              type: string
+            children:
+              type: array
+              items:
+                $ref: '#/definitions/TreeNode'
+        Loop:
+          type: object
+          required:
+            - next
+          properties:
+            next:
+              $ref: '#/definitions/Loop'
+        Holder:
+          type: object
+          required:
+            - loop
+          properties:
+            loop:
+              $ref: '#/definitions/Loop'
+        ";
+        let definitions: Definitions = serde_yaml::from_str(schema).unwrap();
+        assert_eq!(unsatisfiable(&definitions), vec!["Holder", "Loop"]);
+    }
 }
@@ -4,15 +4,24 @@ extern crate lazy_static;
 #[macro_use]
 #[allow(unused_imports)] // macro only
 extern crate maplit;
+extern crate percent_encoding;
 extern crate rand;
 extern crate serde;
 #[macro_use]
 extern crate serde_derive;
+extern crate serde_json;
+extern crate serde_path_to_error;
 extern crate serde_yaml;
 extern crate yaml_rust;
 
+#[macro_use]
+pub mod validator;
+
+pub mod bundle;
 pub mod common;
 pub mod definition;
+pub mod lint;
+pub mod openapi;
 pub mod path;
+pub mod resolver;
 pub mod swagger;
-pub mod validator;
@@ -0,0 +1,463 @@
+use std::collections::{HashMap, HashSet};
+
+use derive_more::Display;
+use serde_yaml::Value;
+
+use common::{Attribute, Referable, TypeDefinition};
+use path::conflict::{conflicts_with, Conflict};
+use path::{In, Operation, Operations, Parameter, Response};
+use resolver::{escape, lookup, unescape};
+use swagger::Swagger;
+
+#[derive(Debug, Clone, PartialEq, Display)]
+pub enum Problem {
+    #[display(fmt = "Reference {} points at nothing", _0)]
+    MissingReference(String),
+    #[display(fmt = "Placeholder {} has no path parameter", _0)]
+    UnboundPlaceholder(String),
+    #[display(fmt = "Path parameter {} is not required", _0)]
+    OptionalPathParameter(String),
+    #[display(fmt = "Operation id {} is already used at {}", _0, _1)]
+    DuplicateOperationId(String, String),
+    #[display(fmt = "Definition {} is never referenced", _0)]
+    UnusedDefinition(String),
+    #[display(fmt = "Required property {} is not defined", _0)]
+    UndefinedRequired(String),
+    /// Another template matches the same requests for the same method.
+    #[display(fmt = "Path {} matches the same requests", _0)]
+    AmbiguousPath(String),
+}
+
+/// A problem found in a document, located by a JSON pointer into it.
+#[derive(Debug, Clone, PartialEq, Display)]
+#[display(fmt = "{}: {}", path, problem)]
+pub struct Diagnostic {
+    pub path: String,
+    pub problem: Problem,
+}
+
+struct Linter<'a> {
+    swagger: &'a Swagger,
+    /// The document as written, which local references point into.
+    document: Value,
+    diagnostics: Vec<Diagnostic>,
+    /// Definitions referenced from outside `definitions`, or from one that is.
+    referenced: HashSet<String>,
+    /// Definitions referenced by each definition.
+    edges: HashMap<String, HashSet<String>>,
+}
+
+impl<'a> Linter<'a> {
+    fn report(&mut self, path: String, problem: Problem) {
+        self.diagnostics.push(Diagnostic { path, problem });
+    }
+
+    fn reference(&mut self, reference: &str, path: &str, owner: Option<&str>) {
+        let pointer = match reference.strip_prefix('#') {
+            Some(pointer) => pointer,
+            None => return,
+        };
+        if let Some(rest) = pointer.strip_prefix("/definitions/") {
+            let name = unescape(rest.split('/').next().unwrap_or_default());
+            match owner {
+                Some(owner) => self
+                    .edges
+                    .entry(owner.to_string())
+                    .or_default()
+                    .insert(name),
+                None => self.referenced.insert(name),
+            };
+        }
+        if lookup(&self.document, pointer).is_none() {
+            self.report(
+                path.to_string(),
+                Problem::MissingReference(reference.into()),
+            );
+        }
+    }
+
+    /// References inside keywords the model does not interpret, e.g. `allOf`.
+    fn value(&mut self, value: &Value, path: &str, owner: Option<&str>) {
+        match value {
+            Value::Mapping(mapping) => {
+                for (key, value) in mapping.iter() {
+                    let key = key.as_str().unwrap_or_default();
+                    match value {
+                        Value::String(reference) if key == "$ref" => {
+                            self.reference(reference, path, owner)
+                        }
+                        _ => self.value(value, &format!("{}/{}", path, escape(key)), owner),
+                    }
+                }
+            }
+            Value::Sequence(sequence) => {
+                for (index, value) in sequence.iter().enumerate() {
+                    self.value(value, &format!("{}/{}", path, index), owner);
+                }
+            }
+            _ => (),
+        }
+    }
+
+    fn schema(&mut self, attribute: &Attribute, path: &str, owner: Option<&str>) {
+        if let Some(reference) = &attribute.reference {
+            self.reference(reference, &format!("{}/$ref", path), owner);
+        }
+        match &attribute.definition {
+            Some(TypeDefinition::Array { items }) => {
+                self.schema(items, &format!("{}/items", path), owner)
+            }
+            Some(TypeDefinition::Object(object)) => {
+                for (index, name) in object.required.iter().flatten().enumerate() {
+                    if !object.properties.contains_key(name) {
+                        let path = format!("{}/required/{}", path, index);
+                        self.report(path, Problem::UndefinedRequired(name.clone()));
+                    }
+                }
+                let mut names: Vec<&String> = object.properties.keys().collect();
+                names.sort();
+                for name in names {
+                    let path = format!("{}/properties/{}", path, escape(name));
+                    self.schema(&object.properties[name], &path, owner);
+                }
+            }
+            _ => (),
+        }
+        // Swagger 2.0 documents write these as `x-oneOf`, `x-anyOf` and
+        // `x-not`, which the composition validators also follow.
+        let composed = vec![
+            (
+                attribute.one_of.is_some(),
+                "oneOf",
+                attribute.one_of_branches(),
+            ),
+            (
+                attribute.any_of.is_some(),
+                "anyOf",
+                attribute.any_of_branches(),
+            ),
+        ];
+        for (modeled, keyword, branches) in composed {
+            let path = extension_path(path, keyword, modeled);
+            match branches {
+                Some(Ok(branches)) => {
+                    for (index, branch) in branches.iter().enumerate() {
+                        self.schema(branch, &format!("{}/{}", path, index), owner);
+                    }
+                }
+                Some(Err(_)) => self.undecoded(attribute, keyword, &path, owner),
+                None => (),
+            }
+        }
+        let path_not = extension_path(path, "not", attribute.not.is_some());
+        match attribute.not_branch() {
+            Some(Ok(not)) => self.schema(&not, &path_not, owner),
+            Some(Err(_)) => self.undecoded(attribute, "not", &path_not, owner),
+            None => (),
+        }
+        for (key, value) in attribute.extra.iter() {
+            self.value(value, &format!("{}/{}", path, escape(key)), owner);
+        }
+    }
+
+    /// References inside an `x-` composition keyword that is not a schema.
+    fn undecoded(&mut self, attribute: &Attribute, keyword: &str, path: &str, owner: Option<&str>) {
+        if let Some(value) = attribute.extensions.get(&format!("x-{}", keyword)) {
+            self.value(value, path, owner);
+        }
+    }
+
+    fn parameter(&mut self, parameter: &Parameter, path: &str) {
+        if parameter.in_ == In::Path && !parameter.required {
+            let problem = Problem::OptionalPathParameter(parameter.name.clone());
+            self.report(path.to_string(), problem);
+        }
+        if let Some(schema) = &parameter.schema {
+            self.schema(schema, &format!("{}/schema", path), None);
+        }
+        self.schema(&parameter.attribute, path, None);
+    }
+
+    fn parameters(&mut self, parameters: &Option<Vec<Referable<Parameter>>>, path: &str) {
+        for (index, parameter) in parameters.iter().flatten().enumerate() {
+            let path = format!("{}/parameters/{}", path, index);
+            match parameter {
+                Referable::Reference { reference } => {
+                    self.reference(reference, &format!("{}/$ref", path), None)
+                }
+                Referable::Inline(parameter) => self.parameter(parameter, &path),
+            }
+        }
+    }
+
+    /// Whether `operation` has a path parameter named `name`, resolving
+    /// references to top-level parameters.
+    fn binds(&self, operations: &Operations, operation: &Operation, name: &str) -> bool {
+        let lists = vec![&operations.parameters, &operation.parameters];
+        let mut parameters = lists.into_iter().flatten().flatten();
+        parameters.any(|parameter| {
+            let parameter = match parameter {
+                Referable::Inline(parameter) => parameter,
+                Referable::Reference { reference } => match self.swagger.parameter(reference) {
+                    Ok(parameter) => parameter,
+                    Err(_) => return false,
+                },
+            };
+            parameter.in_ == In::Path && parameter.name == name
+        })
+    }
+
+    fn operation(&mut self, uri: &str, operations: &Operations, operation: &Operation, path: &str) {
+        for placeholder in placeholders(uri) {
+            if !self.binds(operations, operation, placeholder) {
+                let problem = Problem::UnboundPlaceholder(placeholder.to_string());
+                self.report(path.to_string(), problem);
+            }
+        }
+        self.parameters(&operation.parameters, path);
+        for (status, response) in operation.responses.iter().flatten() {
+            let path = format!("{}/responses/{}", path, status);
+            match response {
+                Referable::Reference { reference } => {
+                    self.reference(reference, &format!("{}/$ref", path), None)
+                }
+                Referable::Inline(response) => self.response(response, &path),
+            }
+        }
+    }
+
+    fn response(&mut self, response: &Response, path: &str) {
+        if let Some(schema) = &response.schema {
+            self.schema(schema, &format!("{}/schema", path), None);
+        }
+        for (name, header) in response.headers.iter().flatten() {
+            self.schema(header, &format!("{}/headers/{}", path, escape(name)), None);
+        }
+    }
+
+    fn unused(&mut self) {
+        let mut reachable: HashSet<String> = HashSet::new();
+        let mut pending: Vec<String> = self.referenced.iter().cloned().collect();
+        while let Some(name) = pending.pop() {
+            if !reachable.insert(name.clone()) {
+                continue;
+            }
+            pending.extend(self.edges.get(&name).into_iter().flatten().cloned());
+        }
+        let definitions = self.swagger.definitions.iter().flat_map(|d| d.keys());
+        let mut unused: Vec<&String> = definitions.filter(|n| !reachable.contains(*n)).collect();
+        unused.sort();
+        for name in unused {
+            let path = format!("#/definitions/{}", escape(name));
+            self.report(path, Problem::UnusedDefinition(name.clone()));
+        }
+    }
+}
+
+/// The pointer to `keyword` below `path`, or to its `x-` extension when the
+/// keyword itself is not written.
+fn extension_path(path: &str, keyword: &str, modeled: bool) -> String {
+    match modeled {
+        true => format!("{}/{}", path, keyword),
+        false => format!("{}/x-{}", path, keyword),
+    }
+}
+
+/// Names of the `{placeholder}`s in a path template.
+fn placeholders(uri: &str) -> Vec<&str> {
+    let mut names = Vec::new();
+    let mut rest = uri;
+    while let Some(start) = rest.find('{') {
+        match rest[start..].find('}') {
+            Some(end) => {
+                names.push(&rest[start + 1..start + end]);
+                rest = &rest[start + end + 1..];
+            }
+            None => break,
+        }
+    }
+    names
+}
+
+/// Checks `swagger` for structural mistakes that deserializing lets
+/// through, such as references to missing definitions, path placeholders
+/// without a parameter or paths that match the same requests.
+///
+/// Diagnostics come in the order the document is walked: definitions, then
+/// top-level parameters and responses, each by name, then paths. Paths
+/// matching the same requests and unused definitions are reported last.
+pub fn lint(swagger: &Swagger) -> Vec<Diagnostic> {
+    let mut linter = Linter {
+        swagger,
+        document: serde_yaml::to_value(swagger).unwrap_or(Value::Null),
+        diagnostics: Vec::new(),
+        referenced: HashSet::new(),
+        edges: HashMap::new(),
+    };
+
+    let mut names: Vec<&String> = swagger.definitions.iter().flat_map(|d| d.keys()).collect();
+    names.sort();
+    for name in names {
+        let definition = &swagger.definitions.as_ref().unwrap()[name];
+        let path = format!("#/definitions/{}", escape(name));
+        linter.schema(definition, &path, Some(name));
+    }
+    let mut names: Vec<&String> = swagger.parameters.iter().flat_map(|p| p.keys()).collect();
+    names.sort();
+    for name in names {
+        let parameter = &swagger.parameters.as_ref().unwrap()[name];
+        linter.parameter(parameter, &format!("#/parameters/{}", escape(name)));
+    }
+    let mut names: Vec<&String> = swagger.responses.iter().flat_map(|r| r.keys()).collect();
+    names.sort();
+    for name in names {
+        let response = &swagger.responses.as_ref().unwrap()[name];
+        linter.response(response, &format!("#/responses/{}", escape(name)));
+    }
+
+    let mut operation_ids: HashMap<&str, String> = HashMap::new();
+    for (uri, operations) in swagger.paths.iter().flatten() {
+        let path = format!("#/paths/{}", escape(uri));
+        linter.parameters(&operations.parameters, &path);
+        for (method, operation) in operations.iter() {
+            let path = format!("{}/{}", path, method.as_str());
+            if let Some(id) = &operation.operation_id {
+                match operation_ids.get(id.as_str()) {
+                    Some(first) => {
+                        let problem = Problem::DuplicateOperationId(id.clone(), first.clone());
+                        linter.report(path.clone(), problem);
+                    }
+                    None => {
+                        operation_ids.insert(id, path.clone());
+                    }
+                }
+            }
+            linter.operation(uri, operations, operation, &path);
+        }
+    }
+
+    let paths = swagger.paths.iter();
+    let found = paths.flat_map(|paths| conflicts_with(paths, swagger.parameters.as_ref()));
+    for conflict in found {
+        if let Conflict::Ambiguous {
+            method,
+            first,
+            second,
+        } = conflict
+        {
+            let path = format!("#/paths/{}/{}", escape(&second), method.as_str());
+            linter.report(path, Problem::AmbiguousPath(first));
+        }
+    }
+
+    linter.unused();
+    linter.diagnostics
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::lint;
+    use swagger::Swagger;
+
+    #[test]
+    fn test_clean() {
+        let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        path.push("src/swagger/test.yaml");
+        let swagger = Swagger::from_path(&path).unwrap();
+        assert_eq!(lint(&swagger), vec![]);
+    }
+
+    #[test]
+    fn test_lint() {
+        let s = "
+        swagger: '2.0'
+        definitions:
+          Pet:
+            type: object
+            required:
+              - id
+              - name
+            properties:
+              id:
+                type: integer
+              owner:
+                $ref: '#/definitions/Owner'
+          Owner:
+            type: object
+            properties:
+              pets:
+                type: array
+                items:
+                  $ref: '#/definitions/Pet'
+              name:
+                $ref: '#/definitions/Pet/properties/tag'
+          Choice:
+            x-oneOf:
+              - $ref: '#/definitions/Gone'
+              - type: object
+                required:
+                  - kind
+            x-not:
+              $ref: '#/definitions/Pet/properties/id'
+          Orphan:
+            allOf:
+              - $ref: '#/definitions/Missing'
+        parameters:
+          petId:
+            name: petId
+            in: path
+            type: integer
+        paths:
+          /pet/{petId}:
+            get:
+              operationId: getPet
+              parameters:
+                - $ref: '#/parameters/petId'
+              responses:
+                '200':
+                  description: A pet
+                  schema:
+                    $ref: '#/definitions/Pet'
+          /pet/{petId}/owner/{ownerId}:
+            get:
+              operationId: getPet
+              parameters:
+                - $ref: '#/parameters/petId'
+                - $ref: '#/parameters/ownerId'
+          /pet/{name}:
+            get:
+              parameters:
+                - name: name
+                  in: path
+                  required: true
+                  type: string
+        ";
+        let swagger: Swagger = s.parse().unwrap();
+        let diagnostics: Vec<String> = lint(&swagger).iter().map(|d| d.to_string()).collect();
+        assert_eq!(
+            diagnostics,
+            vec![
+                "#/definitions/Choice/x-oneOf/0/$ref: Reference #/definitions/Gone points at \
+                 nothing",
+                "#/definitions/Choice/x-oneOf/1/required/0: Required property kind is not \
+                 defined",
+                "#/definitions/Orphan/allOf/0: Reference #/definitions/Missing points at nothing",
+                "#/definitions/Owner/properties/name/$ref: Reference \
+                 #/definitions/Pet/properties/tag points at nothing",
+                "#/definitions/Pet/required/1: Required property name is not defined",
+                "#/parameters/petId: Path parameter petId is not required",
+                "#/paths/~1pet~1{petId}~1owner~1{ownerId}/get: Operation id getPet is already \
+                 used at #/paths/~1pet~1{petId}/get",
+                "#/paths/~1pet~1{petId}~1owner~1{ownerId}/get: Placeholder ownerId has no path \
+                 parameter",
+                "#/paths/~1pet~1{petId}~1owner~1{ownerId}/get/parameters/1/$ref: Reference \
+                 #/parameters/ownerId points at nothing",
+                "#/paths/~1pet~1{petId}/get: Path /pet/{name} matches the same requests",
+                "#/definitions/Choice: Definition Choice is never referenced",
+                "#/definitions/Orphan: Definition Orphan is never referenced",
+            ]
+        );
+    }
+}
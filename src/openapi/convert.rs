@@ -0,0 +1,789 @@
+use std::collections::{BTreeMap, HashMap};
+
+use derive_more::Display;
+use serde_yaml::Value;
+
+use super::security::{ApiKeyIn, OAuthFlow, OAuthFlows, SecurityScheme};
+use super::{Components, Content, Header, MediaType, OpenApi, PathItem, RequestBody, Server};
+use super::{Operation, Response, Responses};
+use common::{Attribute, Extensions, ObjectType, Referable, StringType, TypeDefinition};
+use path::{CollectionFormat, In, Method, Operations, Parameter};
+use path::{Operation as SwaggerOperation, Response as SwaggerResponse};
+use resolver::escape;
+use swagger::security::{self, Flow};
+use swagger::{Scheme, Swagger};
+use validator::reference::Location;
+
+/// Something in the source document that has no exact OpenAPI 3.0
+/// counterpart, located by a JSON pointer into the source document.
+#[derive(Debug, Clone, PartialEq, Display)]
+#[display(fmt = "{}: {}", path, message)]
+pub struct Warning {
+    pub path: String,
+    pub message: String,
+}
+
+const DEFAULT_MEDIA_TYPE: &str = "application/json";
+const URLENCODED: &str = "application/x-www-form-urlencoded";
+const MULTIPART: &str = "multipart/form-data";
+
+fn scheme_name(scheme: Scheme) -> &'static str {
+    match scheme {
+        Scheme::Http => "http",
+        Scheme::Https => "https",
+        Scheme::Ws => "ws",
+        Scheme::Wss => "wss",
+    }
+}
+
+fn server(url: String) -> Server {
+    Server {
+        url,
+        description: None,
+        variables: None,
+        extensions: Extensions::default(),
+    }
+}
+
+/// A body parameter, either inline or named under top-level `parameters`.
+enum Body {
+    Reference(String),
+    Inline(Box<Parameter>),
+}
+
+/// A parameter list sorted by where each entry ends up in OpenAPI 3.0.
+#[derive(Default)]
+struct Split {
+    parameters: Vec<Referable<Parameter>>,
+    body: Option<Body>,
+    form: Vec<Parameter>,
+}
+
+struct Converter<'a> {
+    swagger: &'a Swagger,
+    warnings: Vec<Warning>,
+}
+
+impl<'a> Converter<'a> {
+    fn warn(&mut self, path: &str, message: String) {
+        let path = path.to_string();
+        self.warnings.push(Warning { path, message });
+    }
+
+    fn servers(&self, schemes: Option<&Vec<Scheme>>) -> Option<Vec<Server>> {
+        let swagger = self.swagger;
+        if swagger.host.is_none() && swagger.base_path.is_none() && schemes.is_none() {
+            return None;
+        }
+        let base_path = swagger.base_path.as_deref().unwrap_or("");
+        let host = match &swagger.host {
+            Some(host) => host,
+            None => {
+                let url = if base_path.is_empty() { "/" } else { base_path };
+                return Some(vec![server(url.to_string())]);
+            }
+        };
+        let servers = match schemes {
+            Some(schemes) if !schemes.is_empty() => schemes
+                .iter()
+                .map(|scheme| format!("{}://{}{}", scheme_name(*scheme), host, base_path))
+                .map(server)
+                .collect(),
+            _ => vec![server(format!("//{}{}", host, base_path))],
+        };
+        Some(servers)
+    }
+
+    fn reference(&mut self, reference: &str, path: &str) -> String {
+        match Location::from(reference.to_string()) {
+            Location::Local(name) => format!("#/components/schemas/{}", name),
+            Location::Response(name) => format!("#/components/responses/{}", name),
+            Location::Parameter(name) => format!("#/components/parameters/{}", name),
+            Location::Remote(_, _) => {
+                let message = format!("Reference {} into another document kept as is", reference);
+                self.warn(path, message);
+                reference.to_string()
+            }
+            _ => reference.to_string(),
+        }
+    }
+
+    /// Rewrites `$ref`s in keywords the model does not interpret, such as
+    /// `allOf`.
+    fn value(&mut self, value: &mut Value, path: &str) {
+        match value {
+            Value::Mapping(mapping) => {
+                for (key, value) in mapping.iter_mut() {
+                    match (key.as_str(), value) {
+                        (Some("$ref"), Value::String(reference)) => {
+                            *reference = self.reference(reference, path)
+                        }
+                        (_, value) => self.value(value, path),
+                    }
+                }
+            }
+            Value::Sequence(sequence) => {
+                for value in sequence.iter_mut() {
+                    self.value(value, path);
+                }
+            }
+            _ => (),
+        }
+    }
+
+    fn schema(&mut self, schema: &mut Attribute, path: &str) {
+        if let Some(reference) = &schema.reference {
+            schema.reference = Some(self.reference(reference, path));
+        }
+        if let Some(TypeDefinition::File) = schema.definition {
+            let string_type = StringType {
+                format: Some("binary".to_string()),
+                choices: None,
+            };
+            schema.definition = Some(TypeDefinition::String(string_type));
+        }
+        if let Some(Value::Bool(nullable)) = schema.extensions.remove("x-nullable") {
+            schema.nullable = nullable;
+        }
+        if let Some(Ok(branches)) = schema.one_of_branches() {
+            schema.extensions.remove("x-oneOf");
+            schema.one_of = Some(branches);
+        }
+        if let Some(Ok(branches)) = schema.any_of_branches() {
+            schema.extensions.remove("x-anyOf");
+            schema.any_of = Some(branches);
+        }
+        if let Some(Ok(branch)) = schema.not_branch() {
+            schema.extensions.remove("x-not");
+            schema.not = Some(Box::new(branch));
+        }
+        if let Some(Value::String(name)) = schema.extra.remove("discriminator") {
+            let mut discriminator = serde_yaml::Mapping::new();
+            discriminator.insert("propertyName".into(), Value::String(name));
+            schema
+                .extra
+                .insert("discriminator".into(), Value::Mapping(discriminator));
+        }
+        for value in schema.extra.values_mut() {
+            self.value(value, path);
+        }
+        for child in schema.children_mut() {
+            self.schema(child, path);
+        }
+    }
+
+    /// A non-body parameter, with its type keywords moved under `schema`.
+    fn parameter(&mut self, parameter: &Parameter, path: &str) -> Parameter {
+        let mut schema = parameter.attribute.clone();
+        let mut attribute = Attribute {
+            description: schema.description.take(),
+            extensions: std::mem::take(&mut schema.extensions),
+            ..Attribute::default()
+        };
+        if let Some(value) = schema.extra.remove("allowEmptyValue") {
+            attribute.extra.insert("allowEmptyValue".into(), value);
+        }
+        if let Some(TypeDefinition::Array { .. }) = schema.definition {
+            let style = match (
+                parameter.in_,
+                parameter.collection_format.unwrap_or_default(),
+            ) {
+                (In::Query, CollectionFormat::Csv) => Some(("form", false)),
+                (In::Query, CollectionFormat::Multi) => Some(("form", true)),
+                (In::Query, CollectionFormat::Ssv) => Some(("spaceDelimited", false)),
+                (In::Query, CollectionFormat::Pipes) => Some(("pipeDelimited", false)),
+                (_, CollectionFormat::Csv) => None,
+                (_, format) => {
+                    let format = format.as_str();
+                    let message = format!("collectionFormat {} has no equivalent style", format);
+                    self.warn(path, message);
+                    None
+                }
+            };
+            if let Some((style, explode)) = style {
+                attribute.extra.insert("style".into(), style.into());
+                attribute.extra.insert("explode".into(), explode.into());
+            }
+        }
+        self.schema(&mut schema, path);
+        let schema = match parameter.schema.clone() {
+            Some(mut own) => {
+                self.schema(&mut own, path);
+                own
+            }
+            None => schema,
+        };
+        Parameter {
+            name: parameter.name.clone(),
+            in_: parameter.in_,
+            required: parameter.required,
+            schema: Some(schema),
+            collection_format: None,
+            attribute,
+        }
+    }
+
+    fn split(&mut self, list: Option<&Vec<Referable<Parameter>>>, path: &str) -> Split {
+        let mut split = Split::default();
+        for (index, parameter) in list.into_iter().flatten().enumerate() {
+            let path = format!("{}/parameters/{}", path, index);
+            match parameter {
+                Referable::Reference { reference } => match self.swagger.parameter(reference) {
+                    Ok(target) if target.in_ == In::Body => {
+                        let name = reference.rsplit('/').next().unwrap_or_default();
+                        split.body = Some(Body::Reference(name.to_string()));
+                    }
+                    Ok(target) if target.in_ == In::FormData => split.form.push(target.clone()),
+                    result => {
+                        if let Err(e) = result {
+                            self.warn(&path, e.to_string());
+                        }
+                        let reference = self.reference(reference, &path);
+                        split.parameters.push(Referable::Reference { reference });
+                    }
+                },
+                Referable::Inline(parameter) => match parameter.in_ {
+                    In::Body => split.body = Some(Body::Inline(Box::new(parameter.clone()))),
+                    In::FormData => split.form.push(parameter.clone()),
+                    _ => {
+                        let parameter = self.parameter(parameter, &path);
+                        split.parameters.push(Referable::Inline(parameter));
+                    }
+                },
+            }
+        }
+        split
+    }
+
+    fn content(&mut self, schema: Option<Attribute>, media_types: &[String]) -> Content {
+        let media_types = media_types.iter().map(|media_type| {
+            let media_type_object = MediaType {
+                schema: schema.clone(),
+                ..MediaType::default()
+            };
+            (media_type.clone(), media_type_object)
+        });
+        media_types.collect()
+    }
+
+    fn body(&mut self, parameter: &Parameter, consumes: &[String], path: &str) -> RequestBody {
+        let mut schema = parameter.schema.clone().unwrap_or_default();
+        self.schema(&mut schema, path);
+        RequestBody {
+            description: parameter.attribute.description.clone(),
+            content: self.content(Some(schema), consumes),
+            required: parameter.required,
+            extensions: parameter.attribute.extensions.clone(),
+            extra: Default::default(),
+        }
+    }
+
+    fn form(&mut self, form: &[Parameter], consumes: &[String], path: &str) -> RequestBody {
+        let mut object = ObjectType {
+            properties: HashMap::new(),
+            required: None,
+        };
+        let mut has_file = false;
+        for parameter in form {
+            let mut schema = parameter.attribute.clone();
+            has_file |= schema.definition == Some(TypeDefinition::File);
+            schema.extra.remove("allowEmptyValue");
+            if let Some(format) = parameter.collection_format {
+                if format != CollectionFormat::Multi {
+                    let message = format!(
+                        "collectionFormat {} of form field {} is not translated",
+                        format.as_str(),
+                        parameter.name
+                    );
+                    self.warn(path, message);
+                }
+            }
+            self.schema(&mut schema, path);
+            object.properties.insert(parameter.name.clone(), schema);
+            if parameter.required {
+                let required = object.required.get_or_insert_with(Vec::new);
+                required.push(parameter.name.clone());
+            }
+        }
+        let form_types = [URLENCODED, MULTIPART];
+        let mut media_types: Vec<String> = consumes
+            .iter()
+            .filter(|media_type| form_types.contains(&media_type.as_str()))
+            .cloned()
+            .collect();
+        if media_types.is_empty() {
+            let media_type = if has_file { MULTIPART } else { URLENCODED };
+            media_types.push(media_type.to_string());
+        }
+        let schema = Attribute {
+            definition: Some(TypeDefinition::Object(object)),
+            ..Attribute::default()
+        };
+        RequestBody {
+            description: None,
+            content: self.content(Some(schema), &media_types),
+            required: form.iter().any(|parameter| parameter.required),
+            extensions: Extensions::default(),
+            extra: Default::default(),
+        }
+    }
+
+    fn response(
+        &mut self, response: &SwaggerResponse, produces: &[String], path: &str,
+    ) -> Response {
+        let mut schema = response.schema.clone();
+        if let Some(schema) = schema.as_mut() {
+            self.schema(schema, path);
+        }
+        let mut content = match schema {
+            Some(schema) => self.content(Some(schema), produces),
+            None => Content::new(),
+        };
+        for (media_type, example) in response.examples.iter().flatten() {
+            let media_type_object = content.entry(media_type.clone()).or_default();
+            media_type_object.example = Some(example.clone());
+        }
+        let headers = response.headers.as_ref().map(|headers| {
+            let headers = headers.iter().map(|(name, header)| {
+                let mut schema = header.clone();
+                let header = Header {
+                    description: schema.description.take(),
+                    required: false,
+                    deprecated: false,
+                    extensions: std::mem::take(&mut schema.extensions),
+                    schema: Some(schema),
+                    extra: Default::default(),
+                };
+                (name.clone(), Referable::Inline(header))
+            });
+            headers.collect()
+        });
+        Response {
+            description: response.description.clone(),
+            headers,
+            content: if content.is_empty() {
+                None
+            } else {
+                Some(content)
+            },
+            extensions: response.extensions.clone(),
+            extra: response.extra.clone(),
+        }
+    }
+
+    fn operation(&mut self, operation: &SwaggerOperation, shared: &Split, path: &str) -> Operation {
+        let swagger = self.swagger;
+        let consumes = operation.consumes.as_ref().or(swagger.consumes.as_ref());
+        let consumes = consumes.cloned().unwrap_or_default();
+        let produces = operation.produces.as_ref().or(swagger.produces.as_ref());
+        let produces = match produces {
+            Some(produces) => produces.clone(),
+            None => vec![DEFAULT_MEDIA_TYPE.to_string()],
+        };
+
+        let own = self.split(operation.parameters.as_ref(), path);
+        let mut form: Vec<&Parameter> = shared
+            .form
+            .iter()
+            .filter(|s| !own.form.iter().any(|o| o.name == s.name))
+            .collect();
+        form.extend(own.form.iter());
+        let body = own.body.as_ref().or(shared.body.as_ref());
+        let request_body = match body {
+            Some(_) if !form.is_empty() => {
+                let message = "Body and formData parameters cannot be combined".to_string();
+                self.warn(path, message);
+                None
+            }
+            Some(Body::Reference(name)) => Some(Referable::Reference {
+                reference: format!("#/components/requestBodies/{}", name),
+            }),
+            Some(Body::Inline(parameter)) => {
+                let media_types = if consumes.is_empty() {
+                    vec![DEFAULT_MEDIA_TYPE.to_string()]
+                } else {
+                    consumes.clone()
+                };
+                Some(Referable::Inline(self.body(parameter, &media_types, path)))
+            }
+            None if !form.is_empty() => {
+                let form: Vec<Parameter> = form.into_iter().cloned().collect();
+                Some(Referable::Inline(self.form(&form, &consumes, path)))
+            }
+            None => None,
+        };
+
+        let responses = operation.responses.as_ref().map(|responses| {
+            let mut converted = BTreeMap::new();
+            for (status, response) in responses.iter() {
+                let path = format!("{}/responses/{}", path, escape(status));
+                let response = match response {
+                    Referable::Reference { reference } => Referable::Reference {
+                        reference: self.reference(reference, &path),
+                    },
+                    Referable::Inline(response) => {
+                        Referable::Inline(self.response(response, &produces, &path))
+                    }
+                };
+                converted.insert(status.clone(), response);
+            }
+            Responses::new(converted, responses.extensions.clone())
+        });
+
+        Operation {
+            operation_id: operation.operation_id.clone(),
+            summary: operation.summary.clone(),
+            description: operation.description.clone(),
+            tags: operation.tags.clone(),
+            external_docs: operation.external_docs.clone(),
+            parameters: if own.parameters.is_empty() {
+                None
+            } else {
+                Some(own.parameters)
+            },
+            request_body,
+            responses,
+            deprecated: operation.deprecated,
+            security: operation.security.clone(),
+            servers: operation
+                .schemes
+                .as_ref()
+                .and_then(|s| self.servers(Some(s))),
+            extensions: operation.extensions.clone(),
+            extra: operation.extra.clone(),
+        }
+    }
+
+    fn path_item(&mut self, operations: &Operations, path: &str) -> PathItem {
+        let shared = self.split(operations.parameters.as_ref(), path);
+        let mut item = PathItem {
+            summary: None,
+            description: None,
+            get: None,
+            put: None,
+            post: None,
+            delete: None,
+            options: None,
+            head: None,
+            patch: None,
+            trace: None,
+            servers: None,
+            parameters: None,
+            extensions: operations.extensions.clone(),
+            extra: operations.extra.clone(),
+        };
+        for (method, operation) in operations.iter() {
+            let path = format!("{}/{}", path, method.as_str());
+            let converted = Some(self.operation(operation, &shared, &path));
+            match method {
+                Method::Get => item.get = converted,
+                Method::Put => item.put = converted,
+                Method::Post => item.post = converted,
+                Method::Delete => item.delete = converted,
+                Method::Options => item.options = converted,
+                Method::Head => item.head = converted,
+                Method::Patch => item.patch = converted,
+            }
+        }
+        if !shared.parameters.is_empty() {
+            item.parameters = Some(shared.parameters);
+        }
+        item
+    }
+
+    fn security_scheme(&mut self, scheme: &security::SecurityScheme) -> SecurityScheme {
+        match scheme.clone() {
+            security::SecurityScheme::Basic {
+                description,
+                extensions,
+            } => SecurityScheme::Http {
+                scheme: "basic".to_string(),
+                bearer_format: None,
+                description,
+                extensions,
+            },
+            security::SecurityScheme::ApiKey {
+                name,
+                in_,
+                description,
+                extensions,
+            } => SecurityScheme::ApiKey {
+                name,
+                in_: match in_ {
+                    security::ApiKeyIn::Header => ApiKeyIn::Header,
+                    security::ApiKeyIn::Query => ApiKeyIn::Query,
+                },
+                description,
+                extensions,
+            },
+            security::SecurityScheme::OAuth2 {
+                flow,
+                authorization_url,
+                token_url,
+                scopes,
+                description,
+                extensions,
+            } => {
+                let oauth_flow = Some(OAuthFlow {
+                    authorization_url,
+                    token_url,
+                    refresh_url: None,
+                    scopes,
+                    extensions: Extensions::default(),
+                });
+                let mut flows = OAuthFlows {
+                    implicit: None,
+                    password: None,
+                    client_credentials: None,
+                    authorization_code: None,
+                    extensions: Extensions::default(),
+                };
+                match flow {
+                    Flow::Implicit => flows.implicit = oauth_flow,
+                    Flow::Password => flows.password = oauth_flow,
+                    Flow::Application => flows.client_credentials = oauth_flow,
+                    Flow::AccessCode => flows.authorization_code = oauth_flow,
+                }
+                SecurityScheme::OAuth2 {
+                    flows: Box::new(flows),
+                    description,
+                    extensions,
+                }
+            }
+        }
+    }
+
+    fn components(&mut self) -> Option<Components> {
+        let swagger = self.swagger;
+        let mut components = Components::default();
+        if let Some(definitions) = &swagger.definitions {
+            let mut schemas = definitions.clone();
+            for (name, schema) in schemas.iter_mut() {
+                self.schema(schema, &format!("#/definitions/{}", escape(name)));
+            }
+            components.schemas = Some(schemas);
+        }
+        for (name, parameter) in swagger.parameters.iter().flatten() {
+            let path = format!("#/parameters/{}", escape(name));
+            match parameter.in_ {
+                In::Body => {
+                    let consumes = match &swagger.consumes {
+                        Some(consumes) => consumes.clone(),
+                        None => vec![DEFAULT_MEDIA_TYPE.to_string()],
+                    };
+                    let body = self.body(parameter, &consumes, &path);
+                    let bodies = components.request_bodies.get_or_insert_with(HashMap::new);
+                    bodies.insert(name.clone(), body);
+                }
+                // Copied into each operation that refers to it instead, as
+                // OpenAPI 3.0 has no reusable form field.
+                In::FormData => (),
+                _ => {
+                    let parameter = self.parameter(parameter, &path);
+                    let parameters = components.parameters.get_or_insert_with(HashMap::new);
+                    parameters.insert(name.clone(), parameter);
+                }
+            }
+        }
+        let produces = match &swagger.produces {
+            Some(produces) => produces.clone(),
+            None => vec![DEFAULT_MEDIA_TYPE.to_string()],
+        };
+        for (name, response) in swagger.responses.iter().flatten() {
+            let path = format!("#/responses/{}", escape(name));
+            let response = self.response(response, &produces, &path);
+            let responses = components.responses.get_or_insert_with(HashMap::new);
+            responses.insert(name.clone(), response);
+        }
+        if let Some(definitions) = &swagger.security_definitions {
+            let schemes = definitions
+                .iter()
+                .map(|(name, scheme)| (name.clone(), self.security_scheme(scheme)));
+            components.security_schemes = Some(schemes.collect());
+        }
+        if components == Components::default() {
+            return None;
+        }
+        Some(components)
+    }
+}
+
+/// Translates a Swagger 2.0 document into OpenAPI 3.0.
+///
+/// Definitions move to `components/schemas`, body and form parameters
+/// become request bodies with a content entry per consumed media type,
+/// response schemas get one per produced media type, and host, base path
+/// and schemes become servers. Every `$ref` is rewritten to match. What
+/// cannot be carried over exactly is reported alongside the result.
+pub fn convert(swagger: &Swagger) -> (OpenApi, Vec<Warning>) {
+    let mut converter = Converter {
+        swagger,
+        warnings: Vec::new(),
+    };
+    let paths = swagger.paths.as_ref().map(|paths| {
+        let paths = paths.iter().map(|(uri, operations)| {
+            let path = format!("#/paths/{}", escape(uri));
+            (uri.clone(), converter.path_item(operations, &path))
+        });
+        paths.collect()
+    });
+    let openapi = OpenApi {
+        openapi: "3.0.3".to_string(),
+        info: swagger.info.clone(),
+        servers: converter.servers(swagger.schemes.as_ref()),
+        paths,
+        components: converter.components(),
+        security: swagger.security.clone(),
+        tags: swagger.tags.clone(),
+        external_docs: swagger.external_docs.clone(),
+        extensions: swagger.extensions.clone(),
+        extra: swagger.extra.clone(),
+    };
+    (openapi, converter.warnings)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::convert;
+    use common::{Referable, TypeDefinition};
+    use openapi::security::SecurityScheme;
+    use openapi::OpenApi;
+    use swagger::Swagger;
+
+    #[test]
+    fn test_convert() {
+        let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        path.push("src/swagger/test.yaml");
+        let swagger = Swagger::from_path(&path).unwrap();
+        let (openapi, warnings) = convert(&swagger);
+        assert!(warnings.is_empty());
+
+        let servers: Vec<&str> = openapi
+            .servers
+            .iter()
+            .flatten()
+            .map(|s| s.url.as_str())
+            .collect();
+        assert_eq!(
+            servers,
+            vec![
+                "https://petstore.swagger.io/v2",
+                "http://petstore.swagger.io/v2"
+            ]
+        );
+        let components = openapi.components.as_ref().unwrap();
+        assert!(components.schemas.as_ref().unwrap().contains_key("Pet"));
+        match components
+            .security_schemes
+            .as_ref()
+            .unwrap()
+            .get("petstore_auth")
+        {
+            Some(SecurityScheme::OAuth2 { flows, .. }) => assert!(flows.implicit.is_some()),
+            _ => panic!("Not matched"),
+        }
+
+        let paths = openapi.paths.as_ref().unwrap();
+        let add_pet = paths.get("/pet").unwrap().post.as_ref().unwrap();
+        let body = match add_pet.request_body.as_ref().unwrap() {
+            Referable::Inline(body) => body,
+            _ => panic!("Not matched"),
+        };
+        let mut media_types: Vec<&String> = body.content.keys().collect();
+        media_types.sort();
+        assert_eq!(media_types, vec!["application/json", "application/xml"]);
+        let schema = body.content["application/json"].schema.as_ref().unwrap();
+        assert_eq!(
+            schema.reference.as_ref().unwrap(),
+            "#/components/schemas/Pet"
+        );
+
+        let upload = paths.get("/pet/{petId}/uploadImage").unwrap();
+        let upload = upload.post.as_ref().unwrap();
+        let body = match upload.request_body.as_ref().unwrap() {
+            Referable::Inline(body) => body,
+            _ => panic!("Not matched"),
+        };
+        let schema = body.content["multipart/form-data"].schema.as_ref().unwrap();
+        let file = match schema.definition.as_ref().unwrap() {
+            TypeDefinition::Object(object) => object.properties.get("file").unwrap(),
+            _ => panic!("Not matched"),
+        };
+        match file.definition.as_ref().unwrap() {
+            TypeDefinition::String(string) => assert_eq!(string.format.as_ref().unwrap(), "binary"),
+            _ => panic!("Not matched"),
+        }
+        assert_eq!(upload.parameters.as_ref().unwrap().len(), 1);
+
+        let find = paths
+            .get("/pet/findByStatus")
+            .unwrap()
+            .get
+            .as_ref()
+            .unwrap();
+        let status = find.inline_parameters().next().unwrap();
+        assert!(status.schema.is_some());
+        assert_eq!(
+            status.attribute.extra["explode"],
+            serde_yaml::Value::Bool(true)
+        );
+        let response = match find.response(200).unwrap() {
+            Referable::Inline(response) => response,
+            _ => panic!("Not matched"),
+        };
+        assert!(response
+            .content
+            .as_ref()
+            .unwrap()
+            .contains_key("application/xml"));
+
+        let yaml = serde_yaml::to_string(&openapi).unwrap();
+        let reloaded: OpenApi = yaml.parse().unwrap();
+        assert_eq!(reloaded, openapi);
+    }
+
+    #[test]
+    fn test_warnings() {
+        let s = "
+        swagger: '2.0'
+        parameters:
+          Pet:
+            name: pet
+            in: body
+            schema:
+              $ref: 'common.yaml#/definitions/Pet'
+        paths:
+          /pet:
+            parameters:
+              - name: ids
+                in: header
+                type: array
+                items:
+                  type: integer
+                collectionFormat: pipes
+            post:
+              parameters:
+                - $ref: '#/parameters/Pet'
+                - name: name
+                  in: formData
+                  type: string
+        ";
+        let swagger: Swagger = s.parse().unwrap();
+        let (openapi, warnings) = convert(&swagger);
+        let warnings: Vec<String> = warnings.iter().map(|w| w.to_string()).collect();
+        assert_eq!(
+            warnings,
+            vec![
+                "#/paths/~1pet/parameters/0: collectionFormat pipes has no equivalent style",
+                "#/paths/~1pet/post: Body and formData parameters cannot be combined",
+                "#/parameters/Pet: Reference common.yaml#/definitions/Pet into another \
+                 document kept as is",
+            ]
+        );
+        let components = openapi.components.unwrap();
+        assert!(components.request_bodies.unwrap().contains_key("Pet"));
+        assert!(openapi.servers.is_none());
+    }
+}
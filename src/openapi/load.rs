@@ -0,0 +1,75 @@
+use std::io::Read;
+use std::path::Path;
+use std::str::FromStr;
+
+use super::OpenApi;
+use swagger::load::LoadError;
+use swagger::load::{check_satisfiable, check_version, deserialize, parse, read, read_path};
+
+impl FromStr for OpenApi {
+    type Err = LoadError;
+
+    /// Parses an OpenAPI 3.0.x document, written either as JSON or as YAML,
+    /// rejecting schemas that can never be satisfied.
+    fn from_str(s: &str) -> Result<OpenApi, LoadError> {
+        let openapi = OpenApi::from_str_lenient(s)?;
+        if let Some(components) = &openapi.components {
+            check_satisfiable(&components.schemas)?;
+        }
+        Ok(openapi)
+    }
+}
+
+impl OpenApi {
+    /// Parses a document like `from_str`, but keeps schemas that can never
+    /// be satisfied.
+    pub fn from_str_lenient(s: &str) -> Result<OpenApi, LoadError> {
+        let document = parse(s)?;
+        check_version(&document, "openapi", |version| version.starts_with("3.0."))?;
+        deserialize(document)
+    }
+
+    pub fn from_reader<R: Read>(reader: R) -> Result<OpenApi, LoadError> {
+        read(reader)?.parse()
+    }
+
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<OpenApi, LoadError> {
+        read_path(path.as_ref())?.parse()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use openapi::OpenApi;
+    use swagger::load::LoadError;
+
+    #[test]
+    fn test_errors() {
+        let error = "swagger: '2.0'".parse::<OpenApi>().unwrap_err();
+        assert_eq!(error, LoadError::MissingVersion);
+        let error = "openapi: 3.1.0".parse::<OpenApi>().unwrap_err();
+        assert_eq!(error, LoadError::Version("3.1.0".into()));
+
+        let s = "
+        openapi: 3.0.0
+        components:
+          schemas:
+            Loop:
+              type: object
+              required:
+                - next
+              properties:
+                next:
+                  $ref: '#/components/schemas/Loop'
+        ";
+        assert!(OpenApi::from_str_lenient(s).is_ok());
+        let error = s.parse::<OpenApi>().unwrap_err();
+        assert_eq!(error, LoadError::Unsatisfiable(vec!["Loop".into()]));
+
+        let s =
+            r#"{"openapi": "3.0.1", "paths": {"/pet": {"trace": {"operationId": "tracePet"}}}}"#;
+        let root = OpenApi::from_reader(s.as_bytes()).unwrap();
+        let item = root.paths.as_ref().unwrap().get("/pet").unwrap();
+        assert_eq!(item.iter().count(), 1);
+    }
+}
@@ -0,0 +1,482 @@
+//! OpenAPI 3.0 documents.
+//!
+//! Schemas, parameters and path templates use the same types as Swagger 2.0,
+//! so the validators and `SegmentIter` work on either model. Schemas are
+//! kept under `components/schemas`, which `Location::Component` refers to.
+
+use std::collections::{BTreeMap, HashMap};
+
+use serde_yaml::Value;
+
+use common::{is_false, resolve_references, unmodeled, Attribute, Extensions, Extra, Referable};
+use definition::Definitions;
+use path::{inline_parameters, merge_parameters, Parameter};
+use resolver::ResolveError;
+use swagger::security::SecurityRequirement;
+use swagger::{ExternalDocs, Info, Tag};
+
+pub mod convert;
+pub mod load;
+pub mod security;
+
+use self::security::SecurityScheme;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ServerVariable {
+    pub default: String,
+    #[serde(rename = "enum", skip_serializing_if = "Option::is_none")]
+    pub choices: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(flatten)]
+    pub extensions: Extensions,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Server {
+    pub url: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub variables: Option<BTreeMap<String, ServerVariable>>,
+    #[serde(flatten)]
+    pub extensions: Extensions,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MediaType {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub schema: Option<Attribute>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub example: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub examples: Option<BTreeMap<String, Value>>,
+    #[serde(flatten)]
+    pub extensions: Extensions,
+    #[serde(flatten, deserialize_with = "unmodeled")]
+    pub extra: Extra,
+}
+
+/// Media types keyed by content type, e.g. `application/json`.
+pub type Content = BTreeMap<String, MediaType>;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RequestBody {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub content: Content,
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub required: bool,
+    #[serde(flatten)]
+    pub extensions: Extensions,
+    #[serde(flatten, deserialize_with = "unmodeled")]
+    pub extra: Extra,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Header {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub required: bool,
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub deprecated: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub schema: Option<Attribute>,
+    #[serde(flatten)]
+    pub extensions: Extensions,
+    #[serde(flatten, deserialize_with = "unmodeled")]
+    pub extra: Extra,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Response {
+    pub description: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub headers: Option<BTreeMap<String, Referable<Header>>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content: Option<Content>,
+    #[serde(flatten)]
+    pub extensions: Extensions,
+    #[serde(flatten, deserialize_with = "unmodeled")]
+    pub extra: Extra,
+}
+
+/// Responses keyed by HTTP status code, or `default`.
+pub type Responses = ::common::Responses<Response>;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Operation {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub operation_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub summary: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tags: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub external_docs: Option<ExternalDocs>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parameters: Option<Vec<Referable<Parameter>>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request_body: Option<Referable<RequestBody>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub responses: Option<Responses>,
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub deprecated: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub security: Option<Vec<SecurityRequirement>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub servers: Option<Vec<Server>>,
+    #[serde(flatten)]
+    pub extensions: Extensions,
+    #[serde(flatten, deserialize_with = "unmodeled")]
+    pub extra: Extra,
+}
+
+impl Operation {
+    /// Parameters written inline; references are skipped, so resolve them
+    /// first with `OpenApi::resolve_parameters`.
+    pub fn inline_parameters(&self) -> impl Iterator<Item = &Parameter> {
+        inline_parameters(&self.parameters)
+    }
+
+    /// The response documented for `status`, falling back to `default`.
+    pub fn response(&self, status: u16) -> Option<&Referable<Response>> {
+        let responses = self.responses.as_ref()?;
+        responses
+            .get(&status.to_string())
+            .or_else(|| responses.get("default"))
+    }
+}
+
+/// The methods of a path item, those of Swagger 2.0 and `trace`.
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum Method {
+    Get,
+    Put,
+    Post,
+    Delete,
+    Options,
+    Head,
+    Patch,
+    Trace,
+}
+
+impl Method {
+    /// The name of the method as written in a document, e.g. `get`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Method::Get => "get",
+            Method::Put => "put",
+            Method::Post => "post",
+            Method::Delete => "delete",
+            Method::Options => "options",
+            Method::Head => "head",
+            Method::Patch => "patch",
+            Method::Trace => "trace",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PathItem {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub summary: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub get: Option<Operation>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub put: Option<Operation>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub post: Option<Operation>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub delete: Option<Operation>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub options: Option<Operation>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub head: Option<Operation>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub patch: Option<Operation>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trace: Option<Operation>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub servers: Option<Vec<Server>>,
+    /// Parameters shared by every operation on the path.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parameters: Option<Vec<Referable<Parameter>>>,
+    #[serde(flatten)]
+    pub extensions: Extensions,
+    #[serde(flatten, deserialize_with = "unmodeled")]
+    pub extra: Extra,
+}
+
+impl PathItem {
+    pub fn iter(&self) -> impl Iterator<Item = (Method, &Operation)> {
+        let operations = vec![
+            (Method::Get, &self.get),
+            (Method::Put, &self.put),
+            (Method::Post, &self.post),
+            (Method::Delete, &self.delete),
+            (Method::Options, &self.options),
+            (Method::Head, &self.head),
+            (Method::Patch, &self.patch),
+            (Method::Trace, &self.trace),
+        ];
+        let operations = operations.into_iter();
+        operations.filter_map(|(method, operation)| operation.as_ref().map(|o| (method, o)))
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut Operation> {
+        self.split_mut().1.into_iter()
+    }
+
+    /// The shared parameters alongside each operation, borrowed separately.
+    pub fn split_mut(&mut self) -> (Option<&mut Vec<Referable<Parameter>>>, Vec<&mut Operation>) {
+        let operations = vec![
+            &mut self.get,
+            &mut self.put,
+            &mut self.post,
+            &mut self.delete,
+            &mut self.options,
+            &mut self.head,
+            &mut self.patch,
+            &mut self.trace,
+        ];
+        let operations = operations.into_iter().flatten().collect();
+        (self.parameters.as_mut(), operations)
+    }
+
+    /// The parameters `operation` takes: those shared by the path, overridden
+    /// by any the operation declares with the same name and location.
+    /// References are skipped, so resolve them first with
+    /// `OpenApi::resolve_parameters`.
+    pub fn effective_parameters<'a>(&'a self, operation: &'a Operation) -> Vec<&'a Parameter> {
+        merge_parameters(&self.parameters, &operation.parameters, None)
+    }
+}
+
+pub type Paths = BTreeMap<String, PathItem>;
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Components {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub schemas: Option<Definitions>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub responses: Option<HashMap<String, Response>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parameters: Option<HashMap<String, Parameter>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request_bodies: Option<HashMap<String, RequestBody>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub headers: Option<HashMap<String, Header>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub security_schemes: Option<HashMap<String, SecurityScheme>>,
+    #[serde(flatten)]
+    pub extensions: Extensions,
+    #[serde(flatten, deserialize_with = "unmodeled")]
+    pub extra: Extra,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OpenApi {
+    pub openapi: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub info: Option<Info>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub servers: Option<Vec<Server>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub paths: Option<Paths>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub components: Option<Components>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub security: Option<Vec<SecurityRequirement>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tags: Option<Vec<Tag>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub external_docs: Option<ExternalDocs>,
+    #[serde(flatten)]
+    pub extensions: Extensions,
+    #[serde(flatten, deserialize_with = "unmodeled")]
+    pub extra: Extra,
+}
+
+fn component<'a, T>(
+    map: Option<&'a HashMap<String, T>>, kind: &str, reference: &str,
+) -> Result<&'a T, ResolveError> {
+    let prefix = format!("#/components/{}/", kind);
+    let name = match reference.strip_prefix(prefix.as_str()) {
+        Some(name) => name,
+        None => return Err(ResolveError::Unsupported),
+    };
+    match map.and_then(|map| map.get(name)) {
+        Some(value) => Ok(value),
+        None => Err(ResolveError::Pointer(
+            String::new(),
+            format!("/components/{}/{}", kind, name),
+        )),
+    }
+}
+
+impl OpenApi {
+    /// The schemas under `components/schemas`, which validators look up
+    /// references such as `#/components/schemas/Pet` in.
+    pub fn schemas(&self) -> Option<&Definitions> {
+        self.components.as_ref()?.schemas.as_ref()
+    }
+
+    /// Looks up a parameter reference such as `#/components/parameters/limit`.
+    pub fn parameter(&self, reference: &str) -> Result<&Parameter, ResolveError> {
+        let parameters = self.components.as_ref().and_then(|c| c.parameters.as_ref());
+        component(parameters, "parameters", reference)
+    }
+
+    /// Looks up a response reference such as `#/components/responses/NotFound`.
+    pub fn response(&self, reference: &str) -> Result<&Response, ResolveError> {
+        let responses = self.components.as_ref().and_then(|c| c.responses.as_ref());
+        component(responses, "responses", reference)
+    }
+
+    /// Looks up a request body reference such as
+    /// `#/components/requestBodies/Pet`.
+    pub fn request_body(&self, reference: &str) -> Result<&RequestBody, ResolveError> {
+        let bodies = self
+            .components
+            .as_ref()
+            .and_then(|c| c.request_bodies.as_ref());
+        component(bodies, "requestBodies", reference)
+    }
+
+    /// Replaces every parameter reference in `paths` with a copy of the
+    /// component it points at.
+    pub fn resolve_parameters(&mut self) -> Result<(), ResolveError> {
+        let mut paths = match self.paths.take() {
+            Some(paths) => paths,
+            None => return Ok(()),
+        };
+        let mut lists: Vec<&mut Vec<Referable<Parameter>>> = Vec::new();
+        for item in paths.values_mut() {
+            let (shared, operations) = item.split_mut();
+            lists.extend(shared);
+            lists.extend(operations.into_iter().filter_map(|o| o.parameters.as_mut()));
+        }
+        let parameters = lists.into_iter().flat_map(|l| l.iter_mut());
+        let result = resolve_references(parameters, |r| self.parameter(r));
+        self.paths = Some(paths);
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate yaml_rust;
+
+    use std::path::PathBuf;
+
+    use yaml_rust::YamlLoader;
+
+    use super::{Method, OpenApi};
+    use common::Referable;
+    use openapi::security::SecurityScheme;
+    use path::uri::{Segment, SegmentIter};
+    use validator::reference::{Location, ReferenceValidator};
+    use validator::Validator;
+
+    fn load() -> OpenApi {
+        let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        path.push("src/openapi/test.yaml");
+        OpenApi::from_path(&path).unwrap()
+    }
+
+    #[test]
+    fn test_load_openapi() {
+        let root = load();
+        assert_eq!(root.openapi, "3.0.3");
+        assert_eq!(
+            root.servers.as_ref().unwrap()[0].url,
+            "https://petstore.io/v1"
+        );
+        assert!(root.extra.is_empty());
+
+        let components = root.components.as_ref().unwrap();
+        assert!(components.extra.is_empty());
+        match components.security_schemes.as_ref().unwrap().get("bearer") {
+            Some(SecurityScheme::Http { scheme, .. }) => assert_eq!(scheme, "bearer"),
+            _ => panic!("Not matched"),
+        }
+
+        let paths = root.paths.as_ref().unwrap();
+        let pets = paths.get("/pets").unwrap();
+        let methods: Vec<Method> = pets.iter().map(|(method, _)| method).collect();
+        assert_eq!(methods, vec![Method::Get, Method::Post]);
+        let post = pets.post.as_ref().unwrap();
+        let body = match post.request_body.as_ref().unwrap() {
+            Referable::Reference { reference } => root.request_body(reference).unwrap(),
+            _ => panic!("Not matched"),
+        };
+        let schema = body
+            .content
+            .get("application/json")
+            .unwrap()
+            .schema
+            .as_ref();
+        assert_eq!(
+            schema.unwrap().reference.as_ref().unwrap(),
+            "#/components/schemas/Pet"
+        );
+        match post.response(500).unwrap() {
+            Referable::Reference { reference } => {
+                let response = root.response(reference).unwrap();
+                assert!(response.content.is_some());
+            }
+            _ => panic!("Not matched"),
+        }
+
+        let yaml = serde_yaml::to_string(&root).unwrap();
+        let reloaded: OpenApi = serde_yaml::from_str(&yaml).unwrap();
+        assert_eq!(reloaded, root);
+    }
+
+    #[test]
+    fn test_validate_body() {
+        let root = load();
+        let schemas = root.schemas().unwrap();
+        let location = Location::from("#/components/schemas/Pet".to_string());
+        assert_eq!(location, Location::Component("Pet".into()));
+        let v = ReferenceValidator::new(location, schemas);
+
+        let docs = YamlLoader::load_from_str("{id: 1, name: doggie, tag: ~}").unwrap();
+        assert_eq!(v.validate(&docs[0]), None);
+        let docs = YamlLoader::load_from_str("{id: 1, name: ~}").unwrap();
+        assert!(v.validate(&docs[0]).is_some());
+    }
+
+    #[test]
+    fn test_path_parameters() {
+        let mut root = load();
+        root.resolve_parameters().unwrap();
+        let paths = root.paths.as_ref().unwrap();
+        let (uri, item) = paths.iter().find(|(uri, _)| uri.contains('{')).unwrap();
+        let get = item.get.as_ref().unwrap();
+        let parameters = item.effective_parameters(get);
+        assert_eq!(parameters.len(), 2);
+        let segments: Vec<Segment> = SegmentIter::new(uri, parameters).collect();
+        match segments[1] {
+            Segment::Number(_) => (),
+            _ => panic!("Not matched"),
+        }
+
+        let mut root = load();
+        let item = root.paths.as_mut().unwrap().get_mut("/pets").unwrap();
+        let parameters = item.get.as_mut().unwrap().parameters.as_mut().unwrap();
+        parameters[0] = Referable::Reference {
+            reference: "#/components/parameters/missing".into(),
+        };
+        assert!(root.resolve_parameters().is_err());
+    }
+}
@@ -0,0 +1,291 @@
+use std::collections::{HashMap, HashSet};
+
+use derive_more::Display;
+
+use super::uri::{pieces, segments, splits, tokens, Piece, Segment};
+use super::{Method, Parameter, Paths};
+
+/// Two templates of `Paths` that some request path matches for the same
+/// method.
+#[derive(Debug, Clone, PartialEq, Display)]
+pub enum Conflict {
+    /// Nothing but declaration order decides between the two, as with
+    /// `/user/{username}` and `/user/{id}`.
+    #[display(
+        fmt = "{} {} and {} match the same requests",
+        "method.as_str()",
+        first,
+        second
+    )]
+    Ambiguous {
+        method: Method,
+        first: String,
+        second: String,
+    },
+    /// Requests matching both go to `by`, which has a fixed segment where
+    /// `template` has a placeholder, as `/user/login` has over
+    /// `/user/{username}`.
+    #[display(fmt = "{} {} is shadowed by {}", "method.as_str()", template, by)]
+    Shadowed {
+        method: Method,
+        template: String,
+        by: String,
+    },
+}
+
+/// A template segment, typed by its path parameters when there are any.
+struct Slot<'a> {
+    token: &'a str,
+    pieces: Vec<Piece<'a>>,
+    segment: Option<Segment<'a>>,
+}
+
+impl<'a> Slot<'a> {
+    /// Precedence of the slot when routing, lowest first.
+    fn rank(&self) -> usize {
+        match self.pieces.as_slice() {
+            [Piece::Literal(_)] | [] => 0,
+            [Piece::Placeholder(_)] => 2,
+            _ => 1,
+        }
+    }
+
+    // `Option::is_none_or` needs Rust 1.82.
+    #[allow(clippy::unnecessary_map_or)]
+    fn accepts(&self, value: &str) -> bool {
+        let segment = self.segment.as_ref();
+        match self.pieces.as_slice() {
+            [Piece::Literal(_)] | [] => self.token == value,
+            [Piece::Placeholder(_)] => segment.map_or(true, |s| s.decode(value).is_ok()),
+            pieces => splits(pieces, value, &mut |parts| {
+                let names = pieces.iter().filter_map(|piece| match piece {
+                    Piece::Placeholder(name) => Some(*name),
+                    _ => None,
+                });
+                let mut parts = names.zip(parts.iter());
+                parts.all(|(n, v)| segment.map_or(true, |s| s.decode_part(n, v).is_ok()))
+            }),
+        }
+    }
+
+    /// The values a placeholder is limited to by `enum`, if any.
+    fn choices(&self) -> Option<&HashSet<String>> {
+        match (self.rank(), &self.segment) {
+            (2, Some(Segment::Text(validator))) if !validator.choices.is_empty() => {
+                Some(&validator.choices)
+            }
+            _ => None,
+        }
+    }
+
+    fn literals(&self) -> impl Iterator<Item = &&'a str> {
+        self.pieces.iter().filter_map(|piece| match piece {
+            Piece::Literal(literal) => Some(literal),
+            _ => None,
+        })
+    }
+
+    /// Whether some request segment fits both slots. Placeholders whose
+    /// types cannot be compared are taken to overlap.
+    fn overlaps(&self, other: &Slot) -> bool {
+        if self.rank() == 0 {
+            return other.accepts(self.token);
+        }
+        if other.rank() == 0 {
+            return self.accepts(other.token);
+        }
+        if let Some(choices) = self.choices() {
+            return choices.iter().any(|choice| other.accepts(choice));
+        }
+        if let Some(choices) = other.choices() {
+            return choices.iter().any(|choice| self.accepts(choice));
+        }
+        let number = |slot: &Slot| match (slot.rank(), &slot.segment) {
+            (2, Some(Segment::Number(validator))) => Some((validator.minimum, validator.maximum)),
+            _ => None,
+        };
+        let digits = |slot: &Slot| {
+            let mut literals = slot.literals();
+            literals.all(|literal| literal.chars().all(|c| c.is_ascii_digit() || c == '-'))
+        };
+        match (number(self), number(other)) {
+            (Some((min_a, max_a)), Some((min_b, max_b))) => min_a.max(min_b) <= max_a.min(max_b),
+            (Some(_), None) => digits(other),
+            (None, Some(_)) => digits(self),
+            (None, None) => true,
+        }
+    }
+}
+
+struct Route<'a> {
+    method: Method,
+    template: &'a str,
+    slots: Vec<Slot<'a>>,
+}
+
+fn compare(a: &Route, b: &Route) -> Option<Conflict> {
+    if a.method != b.method || a.slots.len() != b.slots.len() {
+        return None;
+    }
+    let mut slots = a.slots.iter().zip(b.slots.iter());
+    if !slots.clone().all(|(x, y)| x.overlaps(y)) {
+        return None;
+    }
+    let (first, second) = match slots.find(|(x, y)| x.token != y.token) {
+        Some((x, y)) if x.rank() < y.rank() => (a, b),
+        Some((x, y)) if x.rank() > y.rank() => (b, a),
+        _ => {
+            return Some(Conflict::Ambiguous {
+                method: a.method,
+                first: a.template.to_string(),
+                second: b.template.to_string(),
+            })
+        }
+    };
+    Some(Conflict::Shadowed {
+        method: a.method,
+        template: second.template.to_string(),
+        by: first.template.to_string(),
+    })
+}
+
+/// Finds every pair of templates in `paths` that can match the same
+/// request, using the same precedence as `Router`. Path parameters decide
+/// whether two placeholders overlap, so `/pet/{petId}` with an integer
+/// `petId` does not conflict with `/pet/findByStatus`. Parameter references
+/// are skipped; use `conflicts_with` to follow them.
+pub fn conflicts(paths: &Paths) -> Vec<Conflict> {
+    conflicts_with(paths, None)
+}
+
+/// Like `conflicts`, with parameter references looked up in `top_level`,
+/// the `parameters` of the document.
+pub fn conflicts_with(
+    paths: &Paths, top_level: Option<&HashMap<String, Parameter>>,
+) -> Vec<Conflict> {
+    let mut routes = Vec::new();
+    for (template, operations) in paths.iter() {
+        for (method, operation) in operations.iter() {
+            let parameters = operations.resolved_parameters(operation, top_level);
+            let segments = segments(template, parameters);
+            let slots = tokens(template).zip(segments).map(|(token, segment)| Slot {
+                token,
+                pieces: pieces(token),
+                segment,
+            });
+            let slots = slots.collect();
+            routes.push(Route {
+                method,
+                template,
+                slots,
+            });
+        }
+    }
+
+    let mut conflicts = Vec::new();
+    for (index, a) in routes.iter().enumerate() {
+        conflicts.extend(routes[index + 1..].iter().filter_map(|b| compare(a, b)));
+    }
+    conflicts
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::{conflicts, Conflict};
+    use path::{Method, Paths};
+    use swagger::Swagger;
+
+    #[test]
+    fn test_petstore() {
+        let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        path.push("src/swagger/test.yaml");
+        let swagger = Swagger::from_path(&path).unwrap();
+        let conflicts: Vec<String> = conflicts(swagger.paths.as_ref().unwrap())
+            .iter()
+            .map(|c| c.to_string())
+            .collect();
+        assert_eq!(
+            conflicts,
+            vec![
+                "get /user/{username} is shadowed by /user/login",
+                "get /user/{username} is shadowed by /user/logout",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_conflicts() {
+        let s = "
+        /user/{id}:
+          get:
+            parameters:
+              - name: id
+                in: path
+                required: true
+                type: integer
+                minimum: 1
+        /user/{username}:
+          get:
+            parameters:
+              - name: username
+                in: path
+                required: true
+                type: string
+          delete:
+            parameters:
+              - name: username
+                in: path
+                required: true
+                type: string
+        /user/{name}.json:
+          get:
+            parameters:
+              - name: name
+                in: path
+                required: true
+                type: string
+        /order/{id}:
+          get:
+            parameters:
+              - name: id
+                in: path
+                required: true
+                type: integer
+        /order/{status}:
+          get:
+            parameters:
+              - name: status
+                in: path
+                required: true
+                type: string
+                enum:
+                  - placed
+                  - delivered
+        /order/5:
+          get: {}
+        ";
+        let paths: Paths = serde_yaml::from_str(s).unwrap();
+        assert_eq!(
+            conflicts(&paths),
+            vec![
+                Conflict::Shadowed {
+                    method: Method::Get,
+                    template: "/order/{id}".into(),
+                    by: "/order/5".into(),
+                },
+                Conflict::Ambiguous {
+                    method: Method::Get,
+                    first: "/user/{id}".into(),
+                    second: "/user/{username}".into(),
+                },
+                Conflict::Shadowed {
+                    method: Method::Get,
+                    template: "/user/{username}".into(),
+                    by: "/user/{name}.json".into(),
+                },
+            ]
+        );
+    }
+}
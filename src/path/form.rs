@@ -0,0 +1,565 @@
+use std::collections::HashMap;
+use std::str;
+
+use derive_more::Display;
+use yaml_rust::Yaml;
+
+use super::query::{check, coerce, parse_encoded, QueryError};
+use super::{CollectionFormat, In, Parameter, ParameterErrors};
+use common::TypeDefinition;
+use definition::{resolve, Definitions};
+
+#[derive(Debug, Clone, PartialEq, Display)]
+pub enum FormError {
+    #[display(fmt = "Unsupported form content type {}", _0)]
+    UnsupportedContentType(String),
+    /// A body that cannot be read as the form its content type says.
+    #[display(fmt = "Malformed form {}", _0)]
+    Malformed(String),
+    /// A file parameter whose `x-max-size` or `x-content-types` cannot be
+    /// read, a problem with the spec rather than the request.
+    #[display(fmt = "Invalid limit on file parameter {}: {}", _0, _1)]
+    InvalidLimit(String, String),
+    /// Each form parameter that is missing or invalid, with the reason.
+    #[display(fmt = "Invalid form parameters: {}", _0)]
+    InvalidParameters(ParameterErrors),
+}
+
+impl From<QueryError> for FormError {
+    fn from(error: QueryError) -> Self {
+        match error {
+            QueryError::Malformed(text) => FormError::Malformed(text),
+            QueryError::InvalidParameters(errors) => FormError::InvalidParameters(errors),
+        }
+    }
+}
+
+/// A file uploaded as part of a `multipart/form-data` body.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FilePart<'b> {
+    pub filename: Option<String>,
+    /// The content type of the part, `text/plain` when not given.
+    pub content_type: String,
+    pub content: &'b [u8],
+}
+
+/// The fields of a form body, validated against their parameters.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Form<'a, 'b> {
+    /// Values of non-file parameters by name, converted to their type.
+    pub values: HashMap<&'a str, Yaml>,
+    pub files: HashMap<&'a str, FilePart<'b>>,
+}
+
+/// A part of a multipart body.
+struct Part<'b> {
+    name: String,
+    filename: Option<String>,
+    content_type: Option<String>,
+    content: &'b [u8],
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
+/// The value of the parameter `name` in a header value such as
+/// `form-data; name="file"`. Quoted values may hold `;` and
+/// backslash-escaped characters, as in `filename="a;b.png"`.
+fn header_parameter(value: &str, name: &str) -> Option<String> {
+    let mut chars = value.chars().peekable();
+    // The leading token, e.g. `form-data`, is never quoted.
+    chars.by_ref().find(|&c| c == ';')?;
+    while chars.peek().is_some() {
+        let mut key = String::new();
+        while let Some(c) = chars.next_if(|&c| c != '=' && c != ';') {
+            key.push(c);
+        }
+        if chars.next() != Some('=') {
+            continue;
+        }
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+        let mut text = String::new();
+        if chars.next_if_eq(&'"').is_some() {
+            while let Some(c) = chars.next() {
+                match c {
+                    '"' => break,
+                    '\\' => text.extend(chars.next()),
+                    c => text.push(c),
+                }
+            }
+            chars.by_ref().find(|&c| c == ';');
+        } else {
+            while let Some(c) = chars.next_if(|&c| c != ';') {
+                text.push(c);
+            }
+            chars.next();
+            text.truncate(text.trim_end().len());
+        }
+        if key.trim().eq_ignore_ascii_case(name) {
+            return Some(text);
+        }
+    }
+    None
+}
+
+fn part<'b>(head: &str, content: &'b [u8]) -> Result<Part<'b>, FormError> {
+    let mut disposition = None;
+    let mut content_type = None;
+    for line in head.split("\r\n").filter(|line| !line.is_empty()) {
+        let (name, value) = match line.find(':') {
+            Some(index) => (line[..index].trim(), line[index + 1..].trim()),
+            None => return Err(FormError::Malformed(format!("part header {}", line))),
+        };
+        if name.eq_ignore_ascii_case("Content-Disposition") {
+            disposition = Some(value);
+        } else if name.eq_ignore_ascii_case("Content-Type") {
+            content_type = Some(value.to_string());
+        }
+    }
+    let missing = || FormError::Malformed("part without Content-Disposition".into());
+    let disposition = disposition.ok_or_else(missing)?;
+    let name = match header_parameter(disposition, "name") {
+        Some(name) => name,
+        None => return Err(FormError::Malformed("part without a name".into())),
+    };
+    Ok(Part {
+        name,
+        filename: header_parameter(disposition, "filename"),
+        content_type,
+        content,
+    })
+}
+
+/// Splits a `multipart/form-data` body into its parts.
+fn parts<'b>(body: &'b [u8], boundary: &str) -> Result<Vec<Part<'b>>, FormError> {
+    let delimiter = format!("--{}", boundary);
+    let delimiter = delimiter.as_bytes();
+    let unterminated = || FormError::Malformed("multipart body is not terminated".into());
+    let mut rest = match find(body, delimiter) {
+        Some(index) => &body[index + delimiter.len()..],
+        None => return Err(unterminated()),
+    };
+    let mut parts = Vec::new();
+    loop {
+        if rest.starts_with(b"--") {
+            return Ok(parts);
+        }
+        rest = rest.strip_prefix(b"\r\n").ok_or_else(unterminated)?;
+        let end = find(rest, b"\r\n\r\n").ok_or_else(unterminated)?;
+        let head = match str::from_utf8(&rest[..end]) {
+            Ok(head) => head,
+            Err(_) => return Err(FormError::Malformed("part header is not UTF-8".into())),
+        };
+        rest = &rest[end + 4..];
+        let mut next = b"\r\n".to_vec();
+        next.extend_from_slice(delimiter);
+        let end = find(rest, &next).ok_or_else(unterminated)?;
+        parts.push(part(head, &rest[..end])?);
+        rest = &rest[end + next.len()..];
+    }
+}
+
+/// Whether `media_type` is one of those `pattern` names, where `*` stands
+/// for any type or subtype, as in `image/*` or `*/*`.
+fn matches_media_type(pattern: &str, media_type: &str) -> bool {
+    let mut pattern = pattern.splitn(2, '/');
+    let mut media_type = media_type.splitn(2, '/');
+    let fits = |pattern: Option<&str>, part: Option<&str>| match pattern {
+        Some("*") => true,
+        pattern => pattern == part,
+    };
+    fits(pattern.next(), media_type.next()) && fits(pattern.next(), media_type.next())
+}
+
+/// The limits a file parameter sets on uploads: `x-max-size` in bytes and
+/// the `x-content-types` allowed, such as `image/*`.
+struct Limits {
+    max_size: Option<usize>,
+    content_types: Option<Vec<String>>,
+}
+
+fn limits(parameter: &Parameter) -> Result<Limits, FormError> {
+    let extensions = parameter.extensions();
+    let invalid = |key: &str, e: &dyn ToString| {
+        let message = format!("{}: {}", key, e.to_string());
+        FormError::InvalidLimit(parameter.name.clone(), message)
+    };
+    let max_size = match extensions.get_as::<usize>("x-max-size") {
+        Some(Ok(size)) => Some(size),
+        Some(Err(e)) => return Err(invalid("x-max-size", &e)),
+        None => None,
+    };
+    let content_types = match extensions.get_as::<Vec<String>>("x-content-types") {
+        Some(Ok(types)) => Some(types),
+        Some(Err(e)) => return Err(invalid("x-content-types", &e)),
+        None => None,
+    };
+    Ok(Limits {
+        max_size,
+        content_types,
+    })
+}
+
+/// Checks an uploaded file against the limits of its parameter.
+fn check_file(limits: &Limits, file: &FilePart) -> Option<String> {
+    match limits.max_size {
+        Some(size) if file.content.len() > size => return some_str!("file is too large"),
+        _ => (),
+    }
+    let types = limits.content_types.as_ref()?;
+    let content_type = file.content_type.to_ascii_lowercase();
+    let media_type = content_type.split(';').next().unwrap_or_default().trim();
+    let allowed = types
+        .iter()
+        .any(|allowed| matches_media_type(&allowed.to_ascii_lowercase(), media_type));
+    match allowed {
+        true => None,
+        false => some_str!(
+            "file type {} is not one of [{}]",
+            media_type,
+            types.join(", ")
+        ),
+    }
+}
+
+fn is_file(parameter: &Parameter) -> bool {
+    parameter.value_schema().definition == Some(TypeDefinition::File)
+}
+
+/// Whether `parameter` may be given by several parts.
+fn is_multi(parameter: &Parameter) -> bool {
+    parameter.collection_format == Some(CollectionFormat::Multi)
+}
+
+/// The value of a non-file parameter written in the text parts `written`,
+/// of which there is at most one unless the parameter is `multi`.
+fn multipart_value(
+    parameter: &Parameter, written: &[&str], definitions: &Definitions,
+) -> Option<Yaml> {
+    let schema = resolve(parameter.value_schema(), definitions);
+    let last = written.last()?;
+    match &schema.definition {
+        Some(TypeDefinition::Array { items }) => {
+            let texts: Vec<&str> = match parameter.collection_format.unwrap_or_default() {
+                CollectionFormat::Multi => written.to_vec(),
+                format => format.split(last),
+            };
+            let texts = texts
+                .into_iter()
+                .map(|text| coerce(text.to_string(), items, definitions));
+            Some(Yaml::Array(texts.collect()))
+        }
+        _ => Some(coerce(last.to_string(), schema, definitions)),
+    }
+}
+
+/// Parses a request body of `content_type`, either
+/// `application/x-www-form-urlencoded` or `multipart/form-data`, against the
+/// `in: formData` parameters among `parameters`.
+///
+/// Fields are converted and validated as query parameters are. File
+/// parameters must come as parts of a multipart body, and are checked for
+/// presence and against the limits given by their `x-max-size` and
+/// `x-content-types` extensions, which must be readable whatever the
+/// body holds. A field may only be given by several
+/// parts when its parameter is `multi`. Fields no parameter declares are
+/// ignored.
+pub fn parse_form<'a, 'b>(
+    parameters: &[&'a Parameter], content_type: &str, body: &'b [u8], definitions: &Definitions,
+) -> Result<Form<'a, 'b>, FormError> {
+    let parameters = parameters.iter().filter(|p| p.in_ == In::FormData);
+    let (files, fields): (Vec<&Parameter>, Vec<&Parameter>) = parameters.partition(|p| is_file(p));
+    let limits = files
+        .iter()
+        .map(|p| limits(p))
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut form = Form {
+        values: HashMap::new(),
+        files: HashMap::new(),
+    };
+    let mut errors = Vec::new();
+    let media_type = content_type.split(';').next().unwrap_or_default().trim();
+    if media_type.eq_ignore_ascii_case("application/x-www-form-urlencoded") {
+        let text = match str::from_utf8(body) {
+            Ok(text) => text,
+            Err(_) => return Err(FormError::Malformed("body is not UTF-8".into())),
+        };
+        parse_encoded(&fields, text, definitions, &mut form.values, &mut errors)?;
+        for parameter in files.iter().filter(|p| p.required) {
+            errors.push((parameter.name.clone(), "field is required".into()));
+        }
+    } else if media_type.eq_ignore_ascii_case("multipart/form-data") {
+        let boundary = match header_parameter(content_type, "boundary") {
+            Some(boundary) => boundary,
+            None => return Err(FormError::Malformed("content type without boundary".into())),
+        };
+        let parts = parts(body, &boundary)?;
+        for parameter in fields.iter() {
+            let name = parameter.name.as_str();
+            let named: Vec<&Part> = parts.iter().filter(|part| part.name == name).collect();
+            if named.len() > 1 && !is_multi(parameter) {
+                errors.push((name.to_string(), "field is given more than once".into()));
+                continue;
+            }
+            let written: Result<Vec<&str>, _> = named
+                .iter()
+                .map(|part| str::from_utf8(part.content))
+                .collect();
+            let written = match written {
+                Ok(written) => written,
+                Err(_) => {
+                    errors.push((name.to_string(), "field is not UTF-8".into()));
+                    continue;
+                }
+            };
+            let value = multipart_value(parameter, &written, definitions);
+            check(parameter, value, definitions, &mut form.values, &mut errors);
+        }
+        for (parameter, limits) in files.iter().zip(&limits) {
+            let name = parameter.name.as_str();
+            let mut named = parts.iter().filter(|part| part.name == name);
+            let file = match named.next() {
+                Some(_) if named.next().is_some() => {
+                    errors.push((name.to_string(), "field is given more than once".into()));
+                    continue;
+                }
+                Some(part) => FilePart {
+                    filename: part.filename.clone(),
+                    content_type: part.content_type.clone().unwrap_or("text/plain".into()),
+                    content: part.content,
+                },
+                None if parameter.required => {
+                    errors.push((name.to_string(), "field is required".into()));
+                    continue;
+                }
+                None => continue,
+            };
+            match check_file(limits, &file) {
+                Some(message) => errors.push((name.to_string(), message)),
+                None => {
+                    form.files.insert(name, file);
+                }
+            }
+        }
+    } else {
+        return Err(FormError::UnsupportedContentType(content_type.to_string()));
+    }
+    match errors.is_empty() {
+        true => Ok(form),
+        false => Err(FormError::InvalidParameters(ParameterErrors(errors))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate yaml_rust;
+
+    use std::path::PathBuf;
+
+    use yaml_rust::Yaml;
+
+    use super::{parse_form, FormError};
+    use definition::Definitions;
+    use path::{Operations, Parameter};
+    use swagger::Swagger;
+
+    const MULTIPART: &str = "multipart/form-data; boundary=\"XyZ\"";
+
+    fn multipart(parts: &[(&str, &str)]) -> Vec<u8> {
+        let mut body = String::from("preamble\r\n");
+        for (head, content) in parts {
+            body.push_str(&format!("--XyZ\r\n{}\r\n\r\n{}\r\n", head, content));
+        }
+        body.push_str("--XyZ--\r\n");
+        body.into_bytes()
+    }
+
+    #[test]
+    fn test_petstore() {
+        let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        path.push("src/swagger/test.yaml");
+        let swagger = Swagger::from_path(&path).unwrap();
+        let paths = swagger.paths.as_ref().unwrap();
+        let definitions = swagger.definitions.as_ref().unwrap();
+
+        let operations = &paths["/pet/{petId}"];
+        let operation = operations.post.as_ref().unwrap();
+        let parameters = operations.effective_parameters(operation);
+        let content_type = "application/x-www-form-urlencoded; charset=utf-8";
+        let body = b"name=Kitty+Cat&status=sold";
+        let form = parse_form(&parameters, content_type, body, definitions).unwrap();
+        assert_eq!(form.values["name"], Yaml::String("Kitty Cat".into()));
+        assert_eq!(form.values["status"], Yaml::String("sold".into()));
+
+        let operations = &paths["/pet/{petId}/uploadImage"];
+        let operation = operations.post.as_ref().unwrap();
+        let parameters = operations.effective_parameters(operation);
+        let body = multipart(&[
+            (
+                "Content-Disposition: form-data; name=additionalMetadata",
+                "cute",
+            ),
+            (
+                "content-disposition: form-data; name=\"file\"; filename=\"a;b \\\"cat\\\".png\"\r\n\
+                 Content-Type: image/png",
+                "\u{89}PNG",
+            ),
+        ]);
+        let form = parse_form(&parameters, MULTIPART, &body, definitions).unwrap();
+        let metadata = Yaml::String("cute".into());
+        assert_eq!(form.values["additionalMetadata"], metadata);
+        let file = &form.files["file"];
+        assert_eq!(file.filename.as_deref(), Some("a;b \"cat\".png"));
+        assert_eq!(file.content_type, "image/png");
+        assert_eq!(file.content, "\u{89}PNG".as_bytes());
+
+        match parse_form(&parameters, "text/plain", b"", definitions) {
+            Err(error) => assert_eq!(
+                error.to_string(),
+                "Unsupported form content type text/plain"
+            ),
+            _ => panic!("Not matched"),
+        }
+        match parse_form(&parameters, MULTIPART, b"--XyZ\r\n", definitions) {
+            Err(FormError::Malformed(_)) => (),
+            _ => panic!("Not matched"),
+        }
+    }
+
+    #[test]
+    fn test_limits() {
+        let s = "
+        post:
+          parameters:
+            - name: count
+              in: formData
+              required: true
+              type: integer
+            - name: tags
+              in: formData
+              type: array
+              items:
+                type: string
+              collectionFormat: multi
+            - name: avatar
+              in: formData
+              required: true
+              type: file
+              x-max-size: 4
+              x-content-types:
+                - image/*
+            - name: attachment
+              in: formData
+              type: file
+              x-content-types:
+                - '*/*'
+        ";
+        let operations: Operations = serde_yaml::from_str(s).unwrap();
+        let operation = operations.post.as_ref().unwrap();
+        let parameters: Vec<&Parameter> = operation.inline_parameters().collect();
+        let definitions = Definitions::new();
+
+        let avatar = "Content-Disposition: form-data; name=avatar; filename=a.gif\r\n\
+                      Content-Type: image/gif";
+        let body = multipart(&[
+            ("Content-Disposition: form-data; name=count", "2"),
+            ("Content-Disposition: form-data; name=tags", "a"),
+            ("Content-Disposition: form-data; name=tags", "b"),
+            (avatar, "GIF8"),
+        ]);
+        let form = parse_form(&parameters, MULTIPART, &body, &definitions).unwrap();
+        assert_eq!(form.values["count"], Yaml::Integer(2));
+        let tags = vec![Yaml::String("a".into()), Yaml::String("b".into())];
+        assert_eq!(form.values["tags"], Yaml::Array(tags));
+        assert_eq!(form.files["avatar"].content, b"GIF8");
+
+        let body = multipart(&[
+            ("Content-Disposition: form-data; name=count", "two"),
+            (avatar, "GIF89a"),
+        ]);
+        match parse_form(&parameters, MULTIPART, &body, &definitions) {
+            Err(error) => assert_eq!(
+                error.to_string(),
+                "Invalid form parameters: count: field is not integer; avatar: file is too large"
+            ),
+            _ => panic!("Not matched"),
+        }
+        let text = "Content-Disposition: form-data; name=avatar; filename=a.txt";
+        let body = multipart(&[
+            ("Content-Disposition: form-data; name=count", "1"),
+            (text, "hi"),
+        ]);
+        match parse_form(&parameters, MULTIPART, &body, &definitions) {
+            Err(FormError::InvalidParameters(errors)) => assert_eq!(
+                errors.0,
+                vec![(
+                    "avatar".to_string(),
+                    "file type text/plain is not one of [image/*]".to_string()
+                )]
+            ),
+            _ => panic!("Not matched"),
+        }
+        let attachment = "Content-Disposition: form-data; name=attachment; filename=a.txt";
+        let body = multipart(&[
+            ("Content-Disposition: form-data; name=count", "1"),
+            (avatar, "GIF8"),
+            (attachment, "hi"),
+        ]);
+        let form = parse_form(&parameters, MULTIPART, &body, &definitions).unwrap();
+        assert_eq!(form.files["attachment"].content_type, "text/plain");
+
+        let mut body = multipart(&[
+            ("Content-Disposition: form-data; name=count", "#"),
+            ("Content-Disposition: form-data; name=count", "2"),
+            (avatar, "GIF8"),
+            (avatar, "GIF8"),
+        ]);
+        match parse_form(&parameters, MULTIPART, &body, &definitions) {
+            Err(error) => assert_eq!(
+                error.to_string(),
+                "Invalid form parameters: count: field is given more than once; \
+                 avatar: field is given more than once"
+            ),
+            _ => panic!("Not matched"),
+        }
+        let index = body.iter().position(|&byte| byte == b'#').unwrap();
+        body[index] = 0xff;
+        let body = [&body[..index + 1], b"\r\n--XyZ--\r\n"].concat();
+        match parse_form(&parameters, MULTIPART, &body, &definitions) {
+            Err(FormError::InvalidParameters(errors)) => assert_eq!(
+                errors.0,
+                vec![
+                    ("count".to_string(), "field is not UTF-8".to_string()),
+                    ("avatar".to_string(), "field is required".to_string()),
+                ]
+            ),
+            _ => panic!("Not matched"),
+        }
+        let content_type = "application/x-www-form-urlencoded";
+        match parse_form(&parameters, content_type, b"count=1", &definitions) {
+            Err(FormError::InvalidParameters(errors)) => assert_eq!(
+                errors.0,
+                vec![("avatar".to_string(), "field is required".to_string())]
+            ),
+            _ => panic!("Not matched"),
+        }
+
+        let s = "
+        name: avatar
+        in: formData
+        type: file
+        x-max-size: big
+        ";
+        let parameter: Parameter = serde_yaml::from_str(s).unwrap();
+        match parse_form(&[&parameter], MULTIPART, &multipart(&[]), &definitions) {
+            Err(FormError::InvalidLimit(name, message)) => {
+                assert_eq!(name, "avatar");
+                assert!(message.starts_with("x-max-size: invalid type"));
+            }
+            _ => panic!("Not matched"),
+        }
+    }
+}
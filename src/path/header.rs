@@ -0,0 +1,163 @@
+use std::collections::HashMap;
+
+use derive_more::Display;
+use yaml_rust::Yaml;
+
+use super::query::{check, coerce};
+use super::{CollectionFormat, In, Parameter, ParameterErrors};
+use common::TypeDefinition;
+use definition::{resolve, Definitions};
+
+/// Each header parameter that is missing or invalid, with the reason.
+#[derive(Debug, Clone, PartialEq, Display)]
+#[display(fmt = "Invalid header parameters: {}", _0)]
+pub struct HeaderError(pub ParameterErrors);
+
+/// Validates request headers against the `in: header` parameters among
+/// `parameters`.
+///
+/// Header names match parameter names whatever their case. Values are
+/// converted to the declared type and validated by the schema of their
+/// parameter. Arrays are split by their `collectionFormat`, with a header
+/// repeated over several lines taken as one comma separated list, and items
+/// trimmed of surrounding white space. Values are keyed by parameter name as
+/// declared, and headers no parameter declares are ignored.
+pub fn parse_headers<'a, I, N, V>(
+    parameters: &[&'a Parameter], headers: I, definitions: &Definitions,
+) -> Result<HashMap<&'a str, Yaml>, HeaderError>
+where
+    I: IntoIterator<Item = (N, V)>,
+    N: AsRef<str>,
+    V: AsRef<str>,
+{
+    let headers: Vec<(N, V)> = headers.into_iter().collect();
+    let mut values = HashMap::new();
+    let mut errors = Vec::new();
+    for parameter in parameters.iter().filter(|p| p.in_ == In::Header) {
+        let name = parameter.name.as_str();
+        let written = headers
+            .iter()
+            .filter(|(n, _)| n.as_ref().eq_ignore_ascii_case(name));
+        let written: Vec<&str> = written.map(|(_, v)| v.as_ref().trim()).collect();
+        let schema = resolve(parameter.value_schema(), definitions);
+        let value = match (&schema.definition, written.last()) {
+            (_, None) => None,
+            (Some(TypeDefinition::Array { items }), Some(_)) => {
+                let format = match parameter.collection_format.unwrap_or_default() {
+                    CollectionFormat::Multi => CollectionFormat::Csv,
+                    format => format,
+                };
+                let texts = written.iter().flat_map(|value| format.split(value));
+                let texts = texts.map(|text| coerce(text.trim().to_string(), items, definitions));
+                Some(Yaml::Array(texts.collect()))
+            }
+            (_, Some(value)) => Some(coerce(value.to_string(), schema, definitions)),
+        };
+        check(parameter, value, definitions, &mut values, &mut errors);
+    }
+    match errors.is_empty() {
+        true => Ok(values),
+        false => Err(HeaderError(ParameterErrors(errors))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate yaml_rust;
+
+    use std::path::PathBuf;
+
+    use yaml_rust::Yaml;
+
+    use super::{parse_headers, HeaderError};
+    use definition::Definitions;
+    use path::{Operations, Parameter};
+    use swagger::Swagger;
+
+    #[test]
+    fn test_api_key() {
+        let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        path.push("src/swagger/test.yaml");
+        let swagger = Swagger::from_path(&path).unwrap();
+        let operations = &swagger.paths.as_ref().unwrap()["/pet/{petId}"];
+        let operation = operations.delete.as_ref().unwrap();
+        let parameters = operations.effective_parameters(operation);
+        let definitions = swagger.definitions.as_ref().unwrap();
+
+        let headers = vec![("Content-Type", "text/plain"), ("API_KEY", "secret")];
+        let values = parse_headers(&parameters, headers, definitions).unwrap();
+        assert_eq!(values["api_key"], Yaml::String("secret".into()));
+        let headers: Vec<(String, String)> = Vec::new();
+        assert!(parse_headers(&parameters, headers, definitions)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_headers() {
+        let s = "
+        get:
+          parameters:
+            - name: X-Rate-Limit
+              in: header
+              required: true
+              type: integer
+              minimum: 1
+            - name: X-Dry-Run
+              in: header
+              type: boolean
+            - name: X-Tags
+              in: header
+              type: array
+              items:
+                type: string
+            - name: X-Ids
+              in: header
+              type: array
+              items:
+                type: integer
+              collectionFormat: pipes
+        ";
+        let operations: Operations = serde_yaml::from_str(s).unwrap();
+        let operation = operations.get.as_ref().unwrap();
+        let parameters: Vec<&Parameter> = operation.inline_parameters().collect();
+        let definitions = Definitions::new();
+
+        let headers = vec![
+            ("x-rate-limit", " 10 "),
+            ("X-DRY-RUN", "false"),
+            ("x-tags", "a, b"),
+            ("X-Tags", "c"),
+            ("x-ids", "1|2"),
+        ];
+        let values = parse_headers(&parameters, headers, &definitions).unwrap();
+        assert_eq!(values["X-Rate-Limit"], Yaml::Integer(10));
+        assert_eq!(values["X-Dry-Run"], Yaml::Boolean(false));
+        let tags = vec!["a", "b", "c"]
+            .into_iter()
+            .map(|s| Yaml::String(s.into()));
+        assert_eq!(values["X-Tags"], Yaml::Array(tags.collect()));
+        assert_eq!(
+            values["X-Ids"],
+            Yaml::Array(vec![Yaml::Integer(1), Yaml::Integer(2)])
+        );
+
+        let headers = vec![("X-Dry-Run", "maybe"), ("X-Ids", "1|x")];
+        match parse_headers(&parameters, headers, &definitions) {
+            Err(error) => assert_eq!(
+                error.to_string(),
+                "Invalid header parameters: X-Rate-Limit: field is required; \
+                 X-Dry-Run: field is not boolean; X-Ids: field is not integer"
+            ),
+            _ => panic!("Not matched"),
+        }
+        let headers = vec![("X-Rate-Limit", "0")];
+        match parse_headers(&parameters, headers, &definitions) {
+            Err(HeaderError(errors)) => assert_eq!(
+                errors.0,
+                vec![("X-Rate-Limit".to_string(), "field is too small".to_string())]
+            ),
+            _ => panic!("Not matched"),
+        }
+    }
+}
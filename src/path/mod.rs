@@ -1,8 +1,14 @@
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashMap};
 
-use common::Attribute;
+use derive_more::Display;
+use serde_yaml::Value;
 
-#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+use common::{is_false, unmodeled, Attribute, Extensions, Extra, Referable, TypeDefinition};
+use swagger::security::SecurityRequirement;
+use swagger::{ExternalDocs, Scheme};
+use validator::reference::Location;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub enum In {
     Path,
@@ -10,19 +16,113 @@ pub enum In {
     Body,
     Header,
     Query,
+    /// OpenAPI 3.0 only; Swagger 2.0 documents using it fail to load.
+    Cookie,
+}
+
+/// How the items of an array parameter are written in a single string.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CollectionFormat {
+    /// `a,b`
+    #[default]
+    Csv,
+    /// `a b`
+    Ssv,
+    /// Items separated by a tab.
+    Tsv,
+    /// `a|b`
+    Pipes,
+    /// One item per occurrence of the parameter, e.g. `a=1&a=2`.
+    Multi,
 }
 
-#[derive(Debug, PartialEq, Deserialize)]
+impl CollectionFormat {
+    /// The name of the format as written in a document, e.g. `csv`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            CollectionFormat::Csv => "csv",
+            CollectionFormat::Ssv => "ssv",
+            CollectionFormat::Tsv => "tsv",
+            CollectionFormat::Pipes => "pipes",
+            CollectionFormat::Multi => "multi",
+        }
+    }
+
+    /// The character between items, none for `multi`.
+    pub fn separator(self) -> Option<char> {
+        match self {
+            CollectionFormat::Csv => Some(','),
+            CollectionFormat::Ssv => Some(' '),
+            CollectionFormat::Tsv => Some('\t'),
+            CollectionFormat::Pipes => Some('|'),
+            CollectionFormat::Multi => None,
+        }
+    }
+
+    /// The items written in `text`. A `multi` value holds a single item.
+    pub fn split(self, text: &str) -> Vec<&str> {
+        match self.separator() {
+            Some(separator) => text.split(separator).collect(),
+            None => vec![text],
+        }
+    }
+
+    /// Writes `items` as one string. Items of a `multi` array are written
+    /// separately, so this joins them as `csv` would.
+    pub fn join<S: AsRef<str>>(self, items: &[S]) -> String {
+        let separator = self.separator().unwrap_or(',').to_string();
+        let items: Vec<&str> = items.iter().map(|item| item.as_ref()).collect();
+        items.join(&separator)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Parameter {
     pub name: String,
     #[serde(rename = "in")]
     pub in_: In,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "is_false")]
     pub required: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub schema: Option<Attribute>,
+    /// Swagger 2.0 only: how an array value is written.
+    #[serde(rename = "collectionFormat", skip_serializing_if = "Option::is_none")]
+    pub collection_format: Option<CollectionFormat>,
     #[serde(flatten)]
     pub attribute: Attribute,
 }
 
+impl Parameter {
+    /// Vendor extensions of the parameter. They are kept with its schema
+    /// keywords, which are written inline alongside `name` and `in`.
+    pub fn extensions(&self) -> &Extensions {
+        &self.attribute.extensions
+    }
+
+    pub fn extensions_mut(&mut self) -> &mut Extensions {
+        &mut self.attribute.extensions
+    }
+
+    /// The type of a non-body parameter, written inline in Swagger 2.0 and
+    /// under `schema` in OpenAPI 3.0.
+    pub fn definition(&self) -> Option<&TypeDefinition> {
+        match &self.attribute.definition {
+            Some(definition) => Some(definition),
+            None => self.schema.as_ref()?.definition.as_ref(),
+        }
+    }
+
+    /// The schema values of a non-body parameter are validated against,
+    /// the parameter itself in Swagger 2.0 and its `schema` in OpenAPI 3.0.
+    pub fn value_schema(&self) -> &Attribute {
+        match (&self.attribute.definition, &self.schema) {
+            (None, Some(schema)) => schema,
+            _ => &self.attribute,
+        }
+    }
+}
+
 #[derive(Debug, PartialEq, Copy, Clone)]
 pub enum Method {
     Put,
@@ -30,20 +130,126 @@ pub enum Method {
     Get,
     Patch,
     Delete,
+    Head,
+    Options,
+}
+
+impl Method {
+    /// The name of the method as written in a document, e.g. `get`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Method::Put => "put",
+            Method::Post => "post",
+            Method::Get => "get",
+            Method::Patch => "patch",
+            Method::Delete => "delete",
+            Method::Head => "head",
+            Method::Options => "options",
+        }
+    }
+}
+
+/// Each parameter of a request that is missing or invalid, with the reason.
+#[derive(Debug, Clone, PartialEq, Display)]
+#[display(
+    fmt = "{}",
+    "_0.iter().map(|(n, m)| format!(\"{}: {}\", n, m)).collect::<Vec<_>>().join(\"; \")"
+)]
+pub struct ParameterErrors(pub Vec<(String, String)>);
+
+/// A response header, described by the same keywords as a schema.
+pub type Header = Attribute;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Response {
+    pub description: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub schema: Option<Attribute>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub headers: Option<BTreeMap<String, Header>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub examples: Option<BTreeMap<String, Value>>,
+    #[serde(flatten)]
+    pub extensions: Extensions,
+    #[serde(flatten, deserialize_with = "unmodeled")]
+    pub extra: Extra,
 }
 
-#[derive(Debug, PartialEq, Deserialize)]
+/// Responses keyed by HTTP status code, or `default`.
+pub type Responses = ::common::Responses<Response>;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct Operation {
-    pub parameters: Option<Vec<Parameter>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub operation_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub summary: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tags: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub external_docs: Option<ExternalDocs>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub consumes: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub produces: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parameters: Option<Vec<Referable<Parameter>>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub responses: Option<Responses>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub schemes: Option<Vec<Scheme>>,
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub deprecated: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub security: Option<Vec<SecurityRequirement>>,
+    #[serde(flatten)]
+    pub extensions: Extensions,
+    #[serde(flatten, deserialize_with = "unmodeled")]
+    pub extra: Extra,
+}
+
+impl Operation {
+    /// Parameters written inline; references are skipped, so resolve them
+    /// first with `Swagger::resolve_parameters`.
+    pub fn inline_parameters(&self) -> impl Iterator<Item = &Parameter> {
+        inline_parameters(&self.parameters)
+    }
+
+    /// The response documented for `status`, falling back to `default`.
+    pub fn response(&self, status: u16) -> Option<&Referable<Response>> {
+        let responses = self.responses.as_ref()?;
+        responses
+            .get(&status.to_string())
+            .or_else(|| responses.get("default"))
+    }
 }
 
-#[derive(Debug, PartialEq, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Operations {
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub put: Option<Operation>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub post: Option<Operation>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub get: Option<Operation>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub patch: Option<Operation>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub delete: Option<Operation>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub head: Option<Operation>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub options: Option<Operation>,
+    /// Parameters shared by every operation on the path.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parameters: Option<Vec<Referable<Parameter>>>,
+    #[serde(flatten)]
+    pub extensions: Extensions,
+    #[serde(flatten, deserialize_with = "unmodeled")]
+    pub extra: Extra,
 }
 
 pub struct OperationsIter<'a> {
@@ -53,13 +259,102 @@ pub struct OperationsIter<'a> {
 }
 
 impl Operations {
-    pub fn iter(&self) -> OperationsIter {
+    pub fn iter(&self) -> OperationsIter<'_> {
         OperationsIter {
-            operations: &self,
+            operations: self,
             next: Method::Put,
             done: false,
         }
     }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut Operation> {
+        self.split_mut().1.into_iter()
+    }
+
+    /// The shared parameters alongside each operation, borrowed separately.
+    pub fn split_mut(&mut self) -> (Option<&mut Vec<Referable<Parameter>>>, Vec<&mut Operation>) {
+        let operations = vec![
+            &mut self.put,
+            &mut self.post,
+            &mut self.get,
+            &mut self.patch,
+            &mut self.delete,
+            &mut self.head,
+            &mut self.options,
+        ];
+        let operations = operations.into_iter().flatten().collect();
+        (self.parameters.as_mut(), operations)
+    }
+
+    /// Every parameter list on the path: the shared one, then each
+    /// operation's own.
+    pub fn parameter_lists_mut(&mut self) -> Vec<&mut Vec<Referable<Parameter>>> {
+        let (shared, operations) = self.split_mut();
+        let mut lists: Vec<&mut Vec<Referable<Parameter>>> = shared.into_iter().collect();
+        lists.extend(operations.into_iter().filter_map(|o| o.parameters.as_mut()));
+        lists
+    }
+
+    /// The parameters `operation` takes: those shared by the path, overridden
+    /// by any the operation declares with the same name and location.
+    /// References are skipped; use `resolved_parameters` to follow them.
+    pub fn effective_parameters<'a>(&'a self, operation: &'a Operation) -> Vec<&'a Parameter> {
+        merge_parameters(&self.parameters, &operation.parameters, None)
+    }
+
+    /// Like `effective_parameters`, with references such as
+    /// `#/parameters/limit` looked up in `top_level`, the `parameters` of
+    /// the document. References that point at nothing are skipped; `lint`
+    /// reports them.
+    pub fn resolved_parameters<'a>(
+        &'a self, operation: &'a Operation, top_level: Option<&'a HashMap<String, Parameter>>,
+    ) -> Vec<&'a Parameter> {
+        merge_parameters(&self.parameters, &operation.parameters, top_level)
+    }
+}
+
+pub(crate) fn inline_parameters(
+    parameters: &Option<Vec<Referable<Parameter>>>,
+) -> impl Iterator<Item = &Parameter> {
+    parameters
+        .iter()
+        .flatten()
+        .filter_map(|parameter| match parameter {
+            Referable::Inline(parameter) => Some(parameter),
+            _ => None,
+        })
+}
+
+/// The parameters in `parameters`, references looked up in `top_level`.
+fn resolve_parameters<'a>(
+    parameters: &'a Option<Vec<Referable<Parameter>>>,
+    top_level: Option<&'a HashMap<String, Parameter>>,
+) -> impl Iterator<Item = &'a Parameter> {
+    parameters
+        .iter()
+        .flatten()
+        .filter_map(move |parameter| match parameter {
+            Referable::Inline(parameter) => Some(parameter),
+            Referable::Reference { reference } => match Location::from(reference.clone()) {
+                Location::Parameter(name) => top_level?.get(&name),
+                _ => None,
+            },
+        })
+}
+
+/// Parameters of a path merged with those of one of its operations, the
+/// latter winning on the same name and location. References are looked up
+/// in `top_level`, and skipped without it.
+pub(crate) fn merge_parameters<'a>(
+    shared: &'a Option<Vec<Referable<Parameter>>>, own: &'a Option<Vec<Referable<Parameter>>>,
+    top_level: Option<&'a HashMap<String, Parameter>>,
+) -> Vec<&'a Parameter> {
+    let own: Vec<&Parameter> = resolve_parameters(own, top_level).collect();
+    let mut parameters: Vec<&Parameter> = resolve_parameters(shared, top_level)
+        .filter(|s| !own.iter().any(|o| o.name == s.name && o.in_ == s.in_))
+        .collect();
+    parameters.extend(own);
+    parameters
 }
 
 impl<'a> Iterator for OperationsIter<'a> {
@@ -89,9 +384,17 @@ impl<'a> Iterator for OperationsIter<'a> {
                     &self.operations.patch
                 }
                 Method::Delete => {
-                    self.done = true;
+                    self.next = Method::Head;
                     &self.operations.delete
                 }
+                Method::Head => {
+                    self.next = Method::Options;
+                    &self.operations.head
+                }
+                Method::Options => {
+                    self.done = true;
+                    &self.operations.options
+                }
             };
             if option.is_some() {
                 return Some((this, option.as_ref().unwrap()));
@@ -102,7 +405,13 @@ impl<'a> Iterator for OperationsIter<'a> {
 
 pub type Paths = BTreeMap<String, Operations>;
 
+pub mod conflict;
+pub mod form;
+pub mod header;
+pub mod query;
+pub mod router;
 pub mod uri;
+pub mod url;
 
 #[cfg(test)]
 mod tests {
@@ -110,8 +419,9 @@ mod tests {
     use std::fs::File;
     use std::path::PathBuf;
 
-    use super::{Method, Operation, Paths};
-    use path::uri::{Segment, SegmentIter};
+    use super::{CollectionFormat, Method, Operation, Operations, Paths};
+    use common::TypeDefinition;
+    use path::uri::{Fragment, Segment, SegmentIter};
 
     #[test]
     fn test_load_paths() {
@@ -123,16 +433,92 @@ mod tests {
         let pet = paths.get("/pet/{petId}").unwrap();
         let get_pet = pet.get.as_ref().unwrap();
         let get_pet_parameters = get_pet.parameters.as_ref().unwrap();
-        assert_eq!(get_pet_parameters[0].name, "petId");
+        assert_eq!(get_pet.inline_parameters().next().unwrap().name, "petId");
+        assert_eq!(get_pet_parameters.len(), 1);
 
-        let operations: Vec<(&String, Method, &Operation)> = paths
+        let operations: Vec<(&String, &Operations, Method, &Operation)> = paths
             .iter()
             .flat_map(|(uri, v)| {
                 v.iter()
-                    .map(move |(method, operation)| (uri, method, operation))
+                    .map(move |(method, operation)| (uri, v, method, operation))
             })
             .collect();
-        let (uri, _, operation) = operations[0];
-        let _segments: Vec<Segment> = SegmentIter::from((uri, operation)).collect();
+        let (uri, v, _, operation) = operations[0];
+        let _segments: Vec<Segment> = SegmentIter::from((uri, v, operation)).collect();
+    }
+
+    #[test]
+    fn test_collection_format() {
+        let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        path.push("src/path/test.yaml");
+        let file = File::open(path.to_str().unwrap()).unwrap();
+        let root: HashMap<String, Paths> = serde_yaml::from_reader(&file).unwrap();
+        let operation = root["paths"]["/pet/findByStatus"].get.as_ref().unwrap();
+        let status = operation.inline_parameters().next().unwrap();
+        assert_eq!(status.collection_format, Some(CollectionFormat::Multi));
+        assert!(!status.attribute.extra.contains_key("collectionFormat"));
+
+        assert_eq!(CollectionFormat::Pipes.split("a|b"), vec!["a", "b"]);
+        assert_eq!(CollectionFormat::Multi.split("a,b"), vec!["a,b"]);
+        assert_eq!(CollectionFormat::Ssv.join(&["a", "b"]), "a b");
+        assert_eq!(CollectionFormat::default().join(&["a", "b"]), "a,b");
+    }
+
+    #[test]
+    fn test_path_parameters() {
+        let s = "
+        /pet/{petId}:
+          parameters:
+            - name: petId
+              in: path
+              required: true
+              type: integer
+            - name: verbose
+              in: query
+              type: boolean
+          head:
+            parameters:
+              - name: verbose
+                in: query
+                type: string
+          options:
+            summary: Options
+        ";
+        let paths: Paths = serde_yaml::from_str(s).unwrap();
+        let (uri, operations) = paths.iter().next().unwrap();
+        let methods: Vec<Method> = operations.iter().map(|(method, _)| method).collect();
+        assert_eq!(methods, vec![Method::Head, Method::Options]);
+
+        let head = operations.head.as_ref().unwrap();
+        let parameters = operations.effective_parameters(head);
+        assert_eq!(parameters.len(), 2);
+        assert_eq!(parameters[0].name, "petId");
+        match parameters[1].attribute.definition.as_ref().unwrap() {
+            TypeDefinition::String(_) => (),
+            _ => panic!("Not matched"),
+        }
+
+        let segments: Vec<Segment> = SegmentIter::new(uri, parameters.clone()).collect();
+        match segments[1] {
+            Segment::Number(_) => (),
+            _ => panic!("Not matched"),
+        }
+        let options = operations.options.as_ref().unwrap();
+        let segments: Vec<Segment> = SegmentIter::from((uri, operations, options)).collect();
+        match segments[1] {
+            Segment::Number(_) => (),
+            _ => panic!("Not matched"),
+        }
+
+        let segments: Vec<Segment> = SegmentIter::new("/v{petId}.json", parameters).collect();
+        let fragments = match &segments[0] {
+            Segment::Mixed(fragments) => fragments,
+            _ => panic!("Not matched"),
+        };
+        assert_eq!(fragments.len(), 3);
+        match (&fragments[0], &fragments[1]) {
+            (Fragment::Literal("v"), Fragment::Parameter("petId", Segment::Number(_))) => (),
+            _ => panic!("Not matched"),
+        }
     }
 }
@@ -0,0 +1,359 @@
+use std::collections::HashMap;
+
+use derive_more::Display;
+use percent_encoding::percent_decode_str;
+use yaml_rust::Yaml;
+
+use super::{CollectionFormat, In, Parameter, ParameterErrors};
+use common::{Attribute, TypeDefinition};
+use definition::{resolve, Definitions};
+use validator::common::to_validator;
+
+#[derive(Debug, Clone, PartialEq, Display)]
+pub enum QueryError {
+    /// A name or value that is not UTF-8 once percent-decoded.
+    #[display(fmt = "Malformed query {}", _0)]
+    Malformed(String),
+    /// Each query parameter that is missing or invalid, with the reason.
+    #[display(fmt = "Invalid query parameters: {}", _0)]
+    InvalidParameters(ParameterErrors),
+}
+
+/// Decodes a query component, where `+` stands for a space.
+fn decode(text: &str) -> Result<String, QueryError> {
+    let text = text.replace('+', " ");
+    match percent_decode_str(&text).decode_utf8() {
+        Ok(decoded) => Ok(decoded.into_owned()),
+        Err(_) => Err(QueryError::Malformed(text)),
+    }
+}
+
+/// Splits a query string into decoded names and their values as written.
+/// Values are decoded later, once split into array items.
+fn pairs(query: &str) -> Result<Vec<(String, &str)>, QueryError> {
+    let query = query.strip_prefix('?').unwrap_or(query);
+    let mut pairs = Vec::new();
+    for pair in query.split('&').filter(|pair| !pair.is_empty()) {
+        let (name, value) = match pair.find('=') {
+            Some(index) => (&pair[..index], &pair[index + 1..]),
+            None => (pair, ""),
+        };
+        pairs.push((decode(name)?, value));
+    }
+    Ok(pairs)
+}
+
+/// Array items written in `value`. Items of `multi` arrays come one per
+/// occurrence, but commas are accepted there too, as with
+/// `status=available,sold`.
+fn items(format: CollectionFormat, value: &str) -> Result<Vec<String>, QueryError> {
+    match format {
+        // The separator is itself encoded, so decode first.
+        CollectionFormat::Ssv | CollectionFormat::Tsv => {
+            let decoded = decode(value)?;
+            Ok(format
+                .split(&decoded)
+                .into_iter()
+                .map(String::from)
+                .collect())
+        }
+        CollectionFormat::Multi => CollectionFormat::Csv
+            .split(value)
+            .into_iter()
+            .map(decode)
+            .collect(),
+        _ => format.split(value).into_iter().map(decode).collect(),
+    }
+}
+
+/// Converts request text to the type declared by `schema`, following its
+/// reference into `definitions`. Text that does not parse is kept as a
+/// string for the validator to reject.
+pub(crate) fn coerce(text: String, schema: &Attribute, definitions: &Definitions) -> Yaml {
+    match &resolve(schema, definitions).definition {
+        Some(TypeDefinition::Integer(_)) => match text.parse::<i64>() {
+            Ok(number) => Yaml::Integer(number),
+            Err(_) => Yaml::String(text),
+        },
+        Some(TypeDefinition::Boolean) => match text.as_str() {
+            "true" => Yaml::Boolean(true),
+            "false" => Yaml::Boolean(false),
+            _ => Yaml::String(text),
+        },
+        _ => Yaml::String(text),
+    }
+}
+
+/// Validates the value written for `parameter`, if any, recording it in
+/// `values` or why it is missing or invalid in `errors`.
+pub(crate) fn check<'a>(
+    parameter: &'a Parameter, value: Option<Yaml>, definitions: &Definitions,
+    values: &mut HashMap<&'a str, Yaml>, errors: &mut Vec<(String, String)>,
+) {
+    let name = parameter.name.as_str();
+    let value = match value {
+        Some(value) => value,
+        None if parameter.required => {
+            errors.push((name.to_string(), "field is required".to_string()));
+            return;
+        }
+        None => return,
+    };
+    match to_validator(parameter.value_schema(), definitions).validate(&value) {
+        Some(message) => errors.push((name.to_string(), message)),
+        None => {
+            values.insert(name, value);
+        }
+    }
+}
+
+/// Parses `query`, the query string of a request, against the `in: query`
+/// parameters among `parameters`.
+///
+/// Values are percent-decoded, converted to the declared type and validated
+/// by the schema of their parameter. Arrays are split by their
+/// `collectionFormat`. Names no parameter declares are ignored. Every
+/// missing or invalid parameter is reported, not just the first.
+pub fn parse_query<'a>(
+    parameters: &[&'a Parameter], query: &str, definitions: &Definitions,
+) -> Result<HashMap<&'a str, Yaml>, QueryError> {
+    let parameters: Vec<&Parameter> = parameters
+        .iter()
+        .filter(|p| p.in_ == In::Query)
+        .cloned()
+        .collect();
+    let mut values = HashMap::new();
+    let mut errors = Vec::new();
+    parse_encoded(&parameters, query, definitions, &mut values, &mut errors)?;
+    match errors.is_empty() {
+        true => Ok(values),
+        false => Err(QueryError::InvalidParameters(ParameterErrors(errors))),
+    }
+}
+
+/// Checks `parameters` against `text`, written as a query string is, e.g.
+/// an `application/x-www-form-urlencoded` body.
+pub(crate) fn parse_encoded<'a>(
+    parameters: &[&'a Parameter], text: &str, definitions: &Definitions,
+    values: &mut HashMap<&'a str, Yaml>, errors: &mut Vec<(String, String)>,
+) -> Result<(), QueryError> {
+    let pairs = pairs(text)?;
+    for parameter in parameters.iter() {
+        let name = parameter.name.as_str();
+        let mut written = pairs.iter().filter(|(n, _)| n == name).map(|(_, v)| *v);
+        let schema = resolve(parameter.value_schema(), definitions);
+        let value = match &schema.definition {
+            Some(TypeDefinition::Array { items: item_schema }) => {
+                let format = parameter.collection_format.unwrap_or_default();
+                let written: Vec<&str> = match format {
+                    CollectionFormat::Multi => written.collect(),
+                    _ => written.next_back().into_iter().collect(),
+                };
+                if written.is_empty() {
+                    None
+                } else {
+                    let mut array = Vec::new();
+                    for value in written {
+                        let texts = items(format, value)?;
+                        let coerced = texts
+                            .into_iter()
+                            .map(|t| coerce(t, item_schema, definitions));
+                        array.extend(coerced);
+                    }
+                    Some(Yaml::Array(array))
+                }
+            }
+            _ => match written.next_back() {
+                Some(value) => Some(coerce(decode(value)?, schema, definitions)),
+                None => None,
+            },
+        };
+        check(parameter, value, definitions, values, errors);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate yaml_rust;
+
+    use std::path::PathBuf;
+
+    use yaml_rust::Yaml;
+
+    use super::{parse_query, QueryError};
+    use definition::Definitions;
+    use path::{Operations, Parameter};
+    use swagger::Swagger;
+
+    fn strings(items: &[&str]) -> Yaml {
+        Yaml::Array(items.iter().map(|s| Yaml::String(s.to_string())).collect())
+    }
+
+    #[test]
+    fn test_find_by_status() {
+        let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        path.push("src/swagger/test.yaml");
+        let swagger = Swagger::from_path(&path).unwrap();
+        let operations = &swagger.paths.as_ref().unwrap()["/pet/findByStatus"];
+        let operation = operations.get.as_ref().unwrap();
+        let parameters = operations.effective_parameters(operation);
+        let definitions = swagger.definitions.as_ref().unwrap();
+
+        let values = parse_query(&parameters, "status=available,sold", definitions).unwrap();
+        assert_eq!(values["status"], strings(&["available", "sold"]));
+        let query = "?status=pending&status=sold&other=1";
+        let values = parse_query(&parameters, query, definitions).unwrap();
+        assert_eq!(values["status"], strings(&["pending", "sold"]));
+
+        match parse_query(&parameters, "status=lost", definitions) {
+            Err(QueryError::InvalidParameters(errors)) => assert_eq!(errors.0[0].0, "status"),
+            _ => panic!("Not matched"),
+        }
+        match parse_query(&parameters, "", definitions) {
+            Err(error) => assert_eq!(
+                error.to_string(),
+                "Invalid query parameters: status: field is required"
+            ),
+            _ => panic!("Not matched"),
+        }
+    }
+
+    #[test]
+    fn test_coerce() {
+        let s = "
+        get:
+          parameters:
+            - name: limit
+              in: query
+              type: integer
+              maximum: 100
+            - name: verbose
+              in: query
+              type: boolean
+            - name: order
+              in: query
+              type: string
+              enum:
+                - asc
+                - desc
+            - name: ids
+              in: query
+              type: array
+              items:
+                type: integer
+              collectionFormat: pipes
+            - name: words
+              in: query
+              type: array
+              items:
+                type: string
+              collectionFormat: ssv
+        ";
+        let operations: Operations = serde_yaml::from_str(s).unwrap();
+        let operation = operations.get.as_ref().unwrap();
+        let parameters: Vec<&Parameter> = operation.inline_parameters().collect();
+        let definitions = Definitions::new();
+
+        let query = "limit=10&verbose=true&order=desc&ids=1|2&words=a+b%20c";
+        let values = parse_query(&parameters, query, &definitions).unwrap();
+        assert_eq!(values["limit"], Yaml::Integer(10));
+        assert_eq!(values["verbose"], Yaml::Boolean(true));
+        assert_eq!(values["order"], Yaml::String("desc".into()));
+        assert_eq!(
+            values["ids"],
+            Yaml::Array(vec![Yaml::Integer(1), Yaml::Integer(2)])
+        );
+        assert_eq!(values["words"], strings(&["a", "b", "c"]));
+        assert!(!values.contains_key("missing"));
+
+        let query = "limit=1000&verbose=yes&order=up&ids=1|x";
+        match parse_query(&parameters, query, &definitions) {
+            Err(QueryError::InvalidParameters(errors)) => {
+                let names: Vec<&str> = errors.0.iter().map(|(n, _)| n.as_str()).collect();
+                assert_eq!(names, vec!["limit", "verbose", "order", "ids"]);
+                assert_eq!(errors.0[0].1, "field is too large");
+                assert_eq!(errors.0[1].1, "field is not boolean");
+                assert_eq!(errors.0[3].1, "field is not integer");
+            }
+            _ => panic!("Not matched"),
+        }
+        match parse_query(&parameters, "order=%FF", &definitions) {
+            Err(error) => assert_eq!(error, QueryError::Malformed("%FF".into())),
+            _ => panic!("Not matched"),
+        }
+    }
+
+    #[test]
+    fn test_references() {
+        let s = "
+        swagger: '2.0'
+        parameters:
+          limit:
+            name: limit
+            in: query
+            type: integer
+            maximum: 100
+        paths:
+          /pet:
+            get:
+              parameters:
+                - $ref: '#/parameters/limit'
+        ";
+        let swagger: Swagger = s.parse().unwrap();
+        let operations = &swagger.paths.as_ref().unwrap()["/pet"];
+        let operation = operations.get.as_ref().unwrap();
+        let parameters = swagger.effective_parameters(operations, operation);
+        let definitions = Definitions::new();
+
+        let values = parse_query(&parameters, "limit=10", &definitions).unwrap();
+        assert_eq!(values["limit"], Yaml::Integer(10));
+        match parse_query(&parameters, "limit=1000", &definitions) {
+            Err(QueryError::InvalidParameters(errors)) => assert_eq!(errors.0[0].0, "limit"),
+            _ => panic!("Not matched"),
+        }
+    }
+
+    #[test]
+    fn test_schema_references() {
+        let s = "
+        swagger: '2.0'
+        definitions:
+          Id:
+            type: integer
+          Key:
+            $ref: '#/definitions/Id'
+        paths:
+          /pet:
+            get:
+              parameters:
+                - name: ids
+                  in: query
+                  type: array
+                  items:
+                    $ref: '#/definitions/Id'
+                - name: key
+                  in: query
+                  schema:
+                    $ref: '#/definitions/Key'
+        ";
+        let swagger: Swagger = s.parse().unwrap();
+        let operations = &swagger.paths.as_ref().unwrap()["/pet"];
+        let operation = operations.get.as_ref().unwrap();
+        let parameters = swagger.effective_parameters(operations, operation);
+        let definitions = swagger.definitions.as_ref().unwrap();
+
+        let values = parse_query(&parameters, "ids=1,2&key=10", definitions).unwrap();
+        assert_eq!(
+            values["ids"],
+            Yaml::Array(vec![Yaml::Integer(1), Yaml::Integer(2)])
+        );
+        assert_eq!(values["key"], Yaml::Integer(10));
+        match parse_query(&parameters, "key=ten", definitions) {
+            Err(QueryError::InvalidParameters(errors)) => {
+                assert_eq!(errors.0[0].1, "field is not integer")
+            }
+            _ => panic!("Not matched"),
+        }
+    }
+}
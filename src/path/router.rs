@@ -0,0 +1,585 @@
+use std::collections::HashMap;
+
+use derive_more::Display;
+use percent_encoding::percent_decode_str;
+
+use super::uri::{pieces, segments, splits, tokens, ParameterValue, Piece, Segment};
+use super::{Method, Operation, Parameter, ParameterErrors, Paths};
+use swagger::Swagger;
+
+#[derive(Debug, Clone, PartialEq, Display)]
+pub enum RouteError {
+    #[display(fmt = "No path matches")]
+    NotFound,
+    /// The path matches, but only for the methods listed.
+    #[display(fmt = "Method not allowed")]
+    MethodNotAllowed(Vec<Method>),
+    /// Each path parameter that failed to decode, with the reason.
+    #[display(fmt = "Invalid path parameters: {}", _0)]
+    InvalidParameters(ParameterErrors),
+    /// A path segment that is not UTF-8 once percent-decoded.
+    #[display(fmt = "Malformed path segment {}", _0)]
+    Malformed(String),
+}
+
+/// An operation matched by a request path.
+pub struct Route<'a> {
+    pub template: &'a str,
+    pub operation: &'a Operation,
+    /// Path parameters by name, decoded by their type.
+    pub parameters: HashMap<&'a str, ParameterValue>,
+}
+
+struct Endpoint<'a> {
+    method: Method,
+    template: &'a str,
+    operation: &'a Operation,
+    /// Typed segments of the template, by position. None where a
+    /// placeholder has no path parameter, which is then matched untyped.
+    segments: Vec<Option<Segment<'a>>>,
+}
+
+impl<'a> Endpoint<'a> {
+    fn decode(&self, index: usize, name: &str, value: &str) -> Result<ParameterValue, String> {
+        match self.segments.get(index) {
+            Some(Some(segment)) => segment.decode_part(name, value),
+            _ => Ok(ParameterValue::String(value.to_string())),
+        }
+    }
+
+    /// Decodes the values captured for the placeholders of the template.
+    fn bind<'p>(
+        &self, captures: &[Capture<'a, 'p>],
+    ) -> Result<HashMap<&'a str, ParameterValue>, ParameterErrors> {
+        let mut parameters = HashMap::new();
+        let mut errors = Vec::new();
+        for capture in captures.iter() {
+            match self.decode(capture.index, capture.name, capture.value) {
+                Ok(value) => {
+                    parameters.insert(capture.name, value);
+                }
+                Err(message) => errors.push((capture.name.to_string(), message)),
+            }
+        }
+        match errors.is_empty() {
+            true => Ok(parameters),
+            false => Err(ParameterErrors(errors)),
+        }
+    }
+}
+
+#[derive(Default)]
+struct Node<'a> {
+    fixed: HashMap<&'a str, Node<'a>>,
+    templated: Vec<(&'a str, Node<'a>)>,
+    /// Segments mixing literal text and placeholders, by template text.
+    patterns: Vec<(&'a str, Vec<Piece<'a>>, Node<'a>)>,
+    endpoints: Vec<Endpoint<'a>>,
+}
+
+/// A placeholder value captured on the way down the tree.
+struct Capture<'a, 'p> {
+    name: &'a str,
+    index: usize,
+    value: &'p str,
+}
+
+#[derive(Default)]
+struct Search {
+    /// Why the first template matching the path, in order of precedence,
+    /// turned the request down. Methods other templates allow are added
+    /// to a `MethodNotAllowed`.
+    failure: Option<RouteError>,
+}
+
+impl<'a> Node<'a> {
+    fn insert(&mut self, tokens: &[&'a str], endpoint: Endpoint<'a>) {
+        let (token, rest) = match tokens.split_first() {
+            Some(split) => split,
+            None => return self.endpoints.push(endpoint),
+        };
+        let pieces = pieces(token);
+        let child = match pieces.as_slice() {
+            [Piece::Placeholder(name)] => {
+                match self.templated.iter().position(|(n, _)| n == name) {
+                    Some(index) => &mut self.templated[index].1,
+                    None => {
+                        self.templated.push((name, Node::default()));
+                        &mut self.templated.last_mut().unwrap().1
+                    }
+                }
+            }
+            [Piece::Literal(_)] | [] => self.fixed.entry(token).or_default(),
+            _ => match self.patterns.iter().position(|(t, _, _)| t == token) {
+                Some(index) => &mut self.patterns[index].2,
+                None => {
+                    self.patterns.push((token, pieces, Node::default()));
+                    &mut self.patterns.last_mut().unwrap().2
+                }
+            },
+        };
+        child.insert(rest, endpoint)
+    }
+
+    fn find<'p>(
+        &self, method: Method, tokens: &[&'p str], index: usize,
+        captures: &mut Vec<Capture<'a, 'p>>, search: &mut Search,
+    ) -> Option<Route<'a>> {
+        let (token, rest) = match tokens.split_first() {
+            Some(split) => split,
+            None => return self.accept(method, captures, search),
+        };
+        if let Some(child) = self.fixed.get(token) {
+            if let Some(route) = child.find(method, rest, index + 1, captures, search) {
+                return Some(route);
+            }
+        }
+        for (_, pieces, child) in self.patterns.iter() {
+            let names = pieces.iter().filter_map(|piece| match piece {
+                Piece::Placeholder(name) => Some(*name),
+                _ => None,
+            });
+            let names: Vec<&'a str> = names.collect();
+            let mut route = None;
+            splits(pieces, token, &mut |parts| {
+                let depth = captures.len();
+                for (name, value) in names.iter().zip(parts) {
+                    captures.push(Capture { name, index, value });
+                }
+                route = child.find(method, rest, index + 1, captures, search);
+                captures.truncate(depth);
+                route.is_some()
+            });
+            if route.is_some() {
+                return route;
+            }
+        }
+        for (name, child) in self.templated.iter() {
+            captures.push(Capture {
+                name,
+                index,
+                value: token,
+            });
+            let route = child.find(method, rest, index + 1, captures, search);
+            captures.pop();
+            if route.is_some() {
+                return route;
+            }
+        }
+        None
+    }
+
+    fn accept(
+        &self, method: Method, captures: &[Capture<'a, '_>], search: &mut Search,
+    ) -> Option<Route<'a>> {
+        if let Some(endpoint) = self.endpoints.iter().find(|e| e.method == method) {
+            return match endpoint.bind(captures) {
+                Ok(parameters) => Some(Route {
+                    template: endpoint.template,
+                    operation: endpoint.operation,
+                    parameters,
+                }),
+                Err(errors) => {
+                    search
+                        .failure
+                        .get_or_insert(RouteError::InvalidParameters(errors));
+                    None
+                }
+            };
+        }
+        let mut allowed = Vec::new();
+        let mut invalid = None;
+        for endpoint in self.endpoints.iter() {
+            match endpoint.bind(captures) {
+                Ok(_) => allowed.push(endpoint.method),
+                Err(errors) => {
+                    invalid.get_or_insert(errors);
+                }
+            }
+        }
+        match (&mut search.failure, invalid) {
+            (Some(RouteError::MethodNotAllowed(methods)), _) => {
+                for method in allowed {
+                    if !methods.contains(&method) {
+                        methods.push(method);
+                    }
+                }
+            }
+            (Some(_), _) => (),
+            (None, _) if !allowed.is_empty() => {
+                search.failure = Some(RouteError::MethodNotAllowed(allowed));
+            }
+            (None, Some(errors)) => search.failure = Some(RouteError::InvalidParameters(errors)),
+            (None, None) => (),
+        }
+        None
+    }
+}
+
+/// Matches request paths against the templates of `Paths`.
+///
+/// Templates are kept in a prefix tree by segment. A fixed segment is tried
+/// before any placeholder at the same position, so `/pet/findByStatus` wins
+/// over `/pet/{petId}`. Segments mixing text and placeholders, such as
+/// `{name}.{ext}`, come next and are tried for every way the request
+/// segment can be cut to fit them. A placeholder whose value its parameter
+/// rejects leaves the way open for the next candidate. When none takes the
+/// request, the error comes from the first template matching the path, so
+/// `DELETE /pet/findByStatus` is a method not allowed rather than an invalid
+/// `petId`. A router built from a `Swagger` follows parameter references
+/// to its top-level parameters; one built from `Paths` skips them.
+///
+/// Request paths are matched after stripping the base path, dropping empty
+/// segments left by repeated or trailing slashes and percent-decoding each
+/// segment. Decoding comes after splitting, so `%2F` stays inside its
+/// parameter.
+pub struct Router<'a> {
+    root: Node<'a>,
+    base_path: &'a str,
+    parameters: Option<&'a HashMap<String, Parameter>>,
+}
+
+impl<'a> Router<'a> {
+    pub fn new(paths: &'a Paths) -> Self {
+        Router::with_base_path(paths, "/")
+    }
+
+    /// Matches only request paths under `base_path`, such as `/v2`.
+    pub fn with_base_path(paths: &'a Paths, base_path: &'a str) -> Self {
+        let mut router = Router {
+            root: Node::default(),
+            base_path,
+            parameters: None,
+        };
+        router.add(paths);
+        router
+    }
+
+    fn add(&mut self, paths: &'a Paths) {
+        for (template, operations) in paths.iter() {
+            for (method, operation) in operations.iter() {
+                let parameters = operations.resolved_parameters(operation, self.parameters);
+                let endpoint = Endpoint {
+                    method,
+                    template,
+                    operation,
+                    segments: segments(template, parameters),
+                };
+                let tokens: Vec<&str> = tokens(template).collect();
+                self.root.insert(&tokens, endpoint);
+            }
+        }
+    }
+
+    pub fn route(&self, method: Method, path: &str) -> Result<Route<'a>, RouteError> {
+        let mut decoded = Vec::new();
+        for token in tokens(path) {
+            match percent_decode_str(token).decode_utf8() {
+                Ok(token) => decoded.push(token),
+                Err(_) => return Err(RouteError::Malformed(token.to_string())),
+            }
+        }
+        let base = tokens(self.base_path).count();
+        if decoded.len() < base
+            || !tokens(self.base_path)
+                .zip(decoded.iter())
+                .all(|(b, t)| b == t)
+        {
+            return Err(RouteError::NotFound);
+        }
+        let tokens: Vec<&str> = decoded[base..].iter().map(|token| token.as_ref()).collect();
+        let mut search = Search::default();
+        if let Some(route) = self
+            .root
+            .find(method, &tokens, 0, &mut Vec::new(), &mut search)
+        {
+            return Ok(route);
+        }
+        Err(search.failure.unwrap_or(RouteError::NotFound))
+    }
+}
+
+impl<'a> From<&'a Paths> for Router<'a> {
+    fn from(paths: &'a Paths) -> Self {
+        Router::new(paths)
+    }
+}
+
+/// Routes the paths of `swagger` under its `basePath`.
+impl<'a> From<&'a Swagger> for Router<'a> {
+    fn from(swagger: &'a Swagger) -> Self {
+        let mut router = Router {
+            root: Node::default(),
+            base_path: swagger.base_path.as_deref().unwrap_or("/"),
+            parameters: swagger.parameters.as_ref(),
+        };
+        if let Some(paths) = swagger.paths.as_ref() {
+            router.add(paths);
+        }
+        router
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::{RouteError, Router};
+    use path::uri::ParameterValue;
+    use path::{Method, Paths};
+    use swagger::Swagger;
+
+    #[test]
+    fn test_route() {
+        let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        path.push("src/swagger/test.yaml");
+        let swagger = Swagger::from_path(&path).unwrap();
+        let router = Router::new(swagger.paths.as_ref().unwrap());
+
+        let route = router.route(Method::Get, "/pet/findByStatus").unwrap();
+        assert_eq!(route.template, "/pet/findByStatus");
+        assert!(route.parameters.is_empty());
+
+        let route = router.route(Method::Get, "/pet/10").unwrap();
+        assert_eq!(route.template, "/pet/{petId}");
+        assert_eq!(route.operation.operation_id.as_ref().unwrap(), "getPetById");
+        assert_eq!(route.parameters["petId"], ParameterValue::Integer(10));
+
+        let route = router.route(Method::Get, "/store/order/5").unwrap();
+        assert_eq!(route.parameters["orderId"], ParameterValue::Integer(5));
+        match router.route(Method::Get, "/store/order/50") {
+            Err(RouteError::InvalidParameters(errors)) => assert_eq!(
+                errors.0,
+                vec![("orderId".to_string(), "field is too large".to_string())]
+            ),
+            _ => panic!("Not matched"),
+        }
+
+        let route = router.route(Method::Post, "/pet/10/uploadImage").unwrap();
+        assert_eq!(route.template, "/pet/{petId}/uploadImage");
+
+        match router.route(Method::Delete, "/pet/findByStatus") {
+            Err(error) => assert_eq!(error, RouteError::MethodNotAllowed(vec![Method::Get])),
+            _ => panic!("Not matched"),
+        }
+        match router.route(Method::Patch, "/user/login") {
+            Err(error) => assert_eq!(
+                error,
+                RouteError::MethodNotAllowed(vec![Method::Get, Method::Put, Method::Delete])
+            ),
+            _ => panic!("Not matched"),
+        }
+        match router.route(Method::Delete, "/pet/fluffy") {
+            Err(error) => assert_eq!(
+                error.to_string(),
+                "Invalid path parameters: petId: field is not integer"
+            ),
+            _ => panic!("Not matched"),
+        }
+        match router.route(Method::Put, "/store/inventory") {
+            Err(error) => assert_eq!(error, RouteError::MethodNotAllowed(vec![Method::Get])),
+            _ => panic!("Not matched"),
+        }
+        match router.route(Method::Get, "/pet/10/owner") {
+            Err(error) => assert_eq!(error, RouteError::NotFound),
+            _ => panic!("Not matched"),
+        }
+    }
+
+    #[test]
+    fn test_normalize() {
+        let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        path.push("src/swagger/test.yaml");
+        let swagger = Swagger::from_path(&path).unwrap();
+        let router = Router::from(&swagger);
+
+        let route = router.route(Method::Get, "/v2//pet/10/").unwrap();
+        assert_eq!(route.template, "/pet/{petId}");
+        let route = router.route(Method::Get, "/v2/user/john%20doe").unwrap();
+        let username = ParameterValue::String("john doe".into());
+        assert_eq!(route.parameters["username"], username);
+        let route = router.route(Method::Get, "/v2/user/a%2Fb").unwrap();
+        assert_eq!(route.template, "/user/{username}");
+        assert_eq!(
+            route.parameters["username"],
+            ParameterValue::String("a/b".into())
+        );
+        let route = router.route(Method::Get, "/v2/pet/find%42yStatus").unwrap();
+        assert_eq!(route.template, "/pet/findByStatus");
+
+        for path in ["/pet/10", "/v22/pet/10", "/v2/pet%2F10"].iter() {
+            match router.route(Method::Get, path) {
+                Err(error) => assert_eq!(error, RouteError::NotFound),
+                _ => panic!("Not matched"),
+            }
+        }
+        match router.route(Method::Get, "/v2/user/%FF") {
+            Err(error) => assert_eq!(error, RouteError::Malformed("%FF".into())),
+            _ => panic!("Not matched"),
+        }
+    }
+
+    #[test]
+    fn test_backtrack() {
+        let s = "
+        /item/{id}/detail:
+          get:
+            parameters:
+              - name: id
+                in: path
+                required: true
+                type: integer
+        /item/{name}/{part}:
+          get:
+            parameters:
+              - name: name
+                in: path
+                required: true
+                type: string
+              - name: part
+                in: path
+                required: true
+                type: string
+                enum:
+                  - head
+                  - detail
+        /tag/{tag}/{count}:
+          get:
+            parameters:
+              - name: count
+                in: path
+                required: true
+                type: integer
+        ";
+        let paths: Paths = serde_yaml::from_str(s).unwrap();
+        let router = Router::from(&paths);
+        let route = router.route(Method::Get, "/item/1/detail").unwrap();
+        assert_eq!(route.template, "/item/{id}/detail");
+        let route = router.route(Method::Get, "/item/one/detail").unwrap();
+        assert_eq!(route.template, "/item/{name}/{part}");
+        let name = ParameterValue::String("one".into());
+        assert_eq!(route.parameters["name"], name);
+        let route = router.route(Method::Get, "/tag/red/5").unwrap();
+        assert_eq!(
+            route.parameters["tag"],
+            ParameterValue::String("red".into())
+        );
+        assert_eq!(route.parameters["count"], ParameterValue::Integer(5));
+        match router.route(Method::Get, "/item/one/tail") {
+            Err(RouteError::InvalidParameters(errors)) => assert_eq!(errors.0[0].0, "part"),
+            _ => panic!("Not matched"),
+        }
+    }
+
+    #[test]
+    fn test_mixed_segments() {
+        let s = "
+        /files/{name}.{ext}:
+          get:
+            parameters:
+              - name: name
+                in: path
+                required: true
+                type: string
+              - name: ext
+                in: path
+                required: true
+                type: string
+                enum:
+                  - gz
+                  - zip
+        /files/{name}:
+          get:
+            parameters:
+              - name: name
+                in: path
+                required: true
+                type: string
+        /v{version}/items:
+          get:
+            parameters:
+              - name: version
+                in: path
+                required: true
+                type: integer
+                minimum: 1
+        /v{version}/items/{ids}:
+          get:
+            parameters:
+              - name: version
+                in: path
+                required: true
+                type: integer
+              - name: ids
+                in: path
+                required: true
+                type: array
+                items:
+                  type: integer
+                collectionFormat: pipes
+        ";
+        let paths = serde_yaml::from_str(s).unwrap();
+        let router = Router::new(&paths);
+
+        let route = router.route(Method::Get, "/files/archive.tar.gz").unwrap();
+        assert_eq!(route.template, "/files/{name}.{ext}");
+        let name = ParameterValue::String("archive.tar".into());
+        assert_eq!(route.parameters["name"], name);
+        assert_eq!(route.parameters["ext"], ParameterValue::String("gz".into()));
+
+        let route = router.route(Method::Get, "/files/notes.txt").unwrap();
+        assert_eq!(route.template, "/files/{name}");
+
+        let route = router.route(Method::Get, "/v2/items").unwrap();
+        assert_eq!(route.parameters["version"], ParameterValue::Integer(2));
+        match router.route(Method::Get, "/v0/items") {
+            Err(RouteError::InvalidParameters(errors)) => assert_eq!(
+                errors.0,
+                vec![("version".to_string(), "field is too small".to_string())]
+            ),
+            _ => panic!("Not matched"),
+        }
+        let route = router.route(Method::Get, "/v1/items/1|2").unwrap();
+        let ids = vec![ParameterValue::Integer(1), ParameterValue::Integer(2)];
+        assert_eq!(route.parameters["ids"], ParameterValue::Array(ids));
+        match router.route(Method::Get, "/v1/items/1|two") {
+            Err(error) => assert_eq!(
+                error.to_string(),
+                "Invalid path parameters: ids: field is not integer"
+            ),
+            _ => panic!("Not matched"),
+        }
+        match router.route(Method::Get, "/items") {
+            Err(error) => assert_eq!(error, RouteError::NotFound),
+            _ => panic!("Not matched"),
+        }
+    }
+
+    #[test]
+    fn test_references() {
+        let s = "
+        swagger: '2.0'
+        parameters:
+          petId:
+            name: petId
+            in: path
+            required: true
+            type: integer
+        paths:
+          /pet/{petId}:
+            parameters:
+              - $ref: '#/parameters/petId'
+            get:
+              operationId: getPetById
+        ";
+        let swagger: Swagger = s.parse().unwrap();
+        let router = Router::from(&swagger);
+
+        let route = router.route(Method::Get, "/pet/10").unwrap();
+        assert_eq!(route.parameters["petId"], ParameterValue::Integer(10));
+        match router.route(Method::Get, "/pet/fluffy") {
+            Err(RouteError::InvalidParameters(errors)) => assert_eq!(errors.0[0].0, "petId"),
+            _ => panic!("Not matched"),
+        }
+    }
+}
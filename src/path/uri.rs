@@ -1,19 +1,189 @@
 use std::iter::Iterator;
 
-use super::{In, Operation, Parameter};
+use super::{CollectionFormat, In, Operation, Operations, Parameter};
 use common::TypeDefinition;
 use validator::integer::IntegerValidator;
 use validator::string::StringValidator;
+use validator::Validator;
 
 pub enum Segment<'a> {
     Fixed(&'a str),
     Text(StringValidator),
     Number(IntegerValidator),
+    /// Literal text and placeholders sharing a segment, e.g. `{name}.{ext}`.
+    Mixed(Vec<Fragment<'a>>),
+    /// An array, its items split by the collection format and typed by the
+    /// inner segment.
+    List(CollectionFormat, Box<Segment<'a>>),
+}
+
+pub enum Fragment<'a> {
+    Literal(&'a str),
+    /// A placeholder by name, typed as a whole segment would be.
+    Parameter(&'a str, Segment<'a>),
+}
+
+/// A piece of a template segment, before any typing.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Piece<'a> {
+    Literal(&'a str),
+    Placeholder(&'a str),
+}
+
+/// The segments of a path, without the empty ones left by leading,
+/// trailing or repeated slashes.
+pub(crate) fn tokens(path: &str) -> impl Iterator<Item = &str> {
+    path.split('/').filter(|token| !token.is_empty())
+}
+
+/// Splits a template segment into literal text and placeholders. An
+/// unterminated `{` is taken literally.
+pub(crate) fn pieces(token: &str) -> Vec<Piece<'_>> {
+    let mut pieces = Vec::new();
+    let mut rest = token;
+    while !rest.is_empty() {
+        let (start, end) = match rest.find('{') {
+            Some(start) => match rest[start..].find('}') {
+                Some(end) => (start, start + end),
+                None => (rest.len(), rest.len()),
+            },
+            None => (rest.len(), rest.len()),
+        };
+        if start > 0 {
+            pieces.push(Piece::Literal(&rest[..start]));
+        }
+        if start == rest.len() {
+            break;
+        }
+        pieces.push(Piece::Placeholder(&rest[start + 1..end]));
+        rest = &rest[end + 1..];
+    }
+    pieces
+}
+
+/// Tries the ways `value` can be cut to fit `pieces`, each placeholder
+/// taking a non-empty part, until `accept` takes one. Earlier placeholders
+/// take as much as they can first. Cuts are made lazily and only where the
+/// piece that follows can start, so a placeholder followed by literal text
+/// is only cut where that text appears.
+pub(crate) fn splits<'p, F>(pieces: &[Piece], value: &'p str, accept: &mut F) -> bool
+where
+    F: FnMut(&[&'p str]) -> bool,
+{
+    fn split<'p, F>(
+        pieces: &[Piece], value: &'p str, parts: &mut Vec<&'p str>, accept: &mut F,
+    ) -> bool
+    where
+        F: FnMut(&[&'p str]) -> bool,
+    {
+        let rest = match pieces.split_first() {
+            None => return value.is_empty() && accept(parts),
+            Some((Piece::Literal(literal), rest)) => {
+                return match value.strip_prefix(literal) {
+                    Some(value) => split(rest, value, parts, accept),
+                    None => false,
+                };
+            }
+            Some((Piece::Placeholder(_), rest)) => rest,
+        };
+        let fits = |end: usize| match rest.first() {
+            Some(Piece::Literal(literal)) => value[end..].starts_with(literal),
+            Some(Piece::Placeholder(_)) => end < value.len(),
+            None => end == value.len(),
+        };
+        let ends = (1..=value.len()).rev();
+        for end in ends.filter(|&end| value.is_char_boundary(end) && fits(end)) {
+            parts.push(&value[..end]);
+            let found = split(rest, &value[end..], parts, accept);
+            parts.pop();
+            if found {
+                return true;
+            }
+        }
+        false
+    }
+    split(pieces, value, &mut Vec::new(), accept)
+}
+
+/// The segments of `template`, one for each of its tokens, typed by the path
+/// parameters in `parameters`. A segment with a placeholder no parameter
+/// describes has none, and takes any value.
+pub(crate) fn segments<'a>(
+    template: &'a str, parameters: Vec<&'a Parameter>,
+) -> Vec<Option<Segment<'a>>> {
+    let mut segments = SegmentIter::new(template, parameters);
+    tokens(template).map(|_| segments.next()).collect()
+}
+
+/// A path parameter decoded by the type of its segment.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParameterValue {
+    Integer(i64),
+    String(String),
+    Array(Vec<ParameterValue>),
+}
+
+impl<'a> Segment<'a> {
+    /// Decodes `value` captured by this segment and validates it. Fixed
+    /// segments carry no type, so their value is kept as a string.
+    pub fn decode(&self, value: &str) -> Result<ParameterValue, String> {
+        match self {
+            Segment::Number(validator) => {
+                let number = match value.parse::<i64>() {
+                    Ok(number) => number,
+                    Err(_) => return Err("field is not integer".to_string()),
+                };
+                match validator.validate(&number) {
+                    Some(message) => Err(message),
+                    None => Ok(ParameterValue::Integer(number)),
+                }
+            }
+            Segment::Text(validator) => match validator.validate(value) {
+                Some(message) => Err(message),
+                None => Ok(ParameterValue::String(value.to_string())),
+            },
+            Segment::List(format, item) => {
+                let items = format.split(value).into_iter().map(|v| item.decode(v));
+                Ok(ParameterValue::Array(items.collect::<Result<_, _>>()?))
+            }
+            Segment::Fixed(_) | Segment::Mixed(_) => Ok(ParameterValue::String(value.to_string())),
+        }
+    }
+
+    /// Decodes the part of a segment captured by the placeholder `name`.
+    pub fn decode_part(&self, name: &str, value: &str) -> Result<ParameterValue, String> {
+        let fragments = match self {
+            Segment::Mixed(fragments) => fragments,
+            _ => return self.decode(value),
+        };
+        for fragment in fragments.iter() {
+            if let Fragment::Parameter(fragment_name, segment) = fragment {
+                if *fragment_name == name {
+                    return segment.decode(value);
+                }
+            }
+        }
+        Ok(ParameterValue::String(value.to_string()))
+    }
+}
+
+/// The segment for a value of type `definition`, untyped text when it is
+/// not a scalar.
+fn scalar<'a>(definition: Option<&TypeDefinition>) -> Segment<'a> {
+    match definition {
+        Some(TypeDefinition::Integer(integer_type)) => {
+            Segment::Number(IntegerValidator::from(integer_type))
+        }
+        Some(TypeDefinition::String(string_type)) => {
+            Segment::Text(StringValidator::from(string_type))
+        }
+        _ => Segment::Text(StringValidator::default()),
+    }
 }
 
 pub struct SegmentIter<'a> {
     tokens: Vec<&'a str>,
-    parameters: &'a Option<Vec<Parameter>>,
+    parameters: Vec<&'a Parameter>,
     token_index: usize,
 }
 
@@ -26,42 +196,62 @@ impl<'a> Iterator for SegmentIter<'a> {
         }
         let token = self.tokens[self.token_index];
         self.token_index += 1;
-        if !token.starts_with("{") || !token.ends_with("}") {
-            return Some(Segment::Fixed(token));
+        let pieces = pieces(token);
+        match pieces.as_slice() {
+            [Piece::Placeholder(name)] => self.typed(name),
+            [Piece::Literal(_)] | [] => Some(Segment::Fixed(token)),
+            _ => {
+                let mut fragments = Vec::with_capacity(pieces.len());
+                for piece in pieces {
+                    fragments.push(match piece {
+                        Piece::Literal(literal) => Fragment::Literal(literal),
+                        Piece::Placeholder(name) => Fragment::Parameter(name, self.typed(name)?),
+                    });
+                }
+                Some(Segment::Mixed(fragments))
+            }
         }
+    }
+}
 
-        let token = &token[1..token.len() - 1];
-        let parameters = match self.parameters {
-            Some(parameters) => parameters,
-            None => return Some(Segment::Fixed(token)),
-        };
+impl<'a> SegmentIter<'a> {
+    /// The segment for the placeholder `name`, typed by its path parameter.
+    fn typed(&self, name: &'a str) -> Option<Segment<'a>> {
+        if self.parameters.is_empty() {
+            return Some(Segment::Fixed(name));
+        }
 
-        for parameter in parameters.iter() {
-            if parameter.name != token || parameter.in_ != In::Path {
+        for parameter in self.parameters.iter() {
+            if parameter.name != name || parameter.in_ != In::Path {
                 continue;
             }
-            return match parameter.attribute.definition.as_ref().unwrap() {
-                TypeDefinition::Integer(integer_type) => {
-                    Some(Segment::Number(IntegerValidator::from(integer_type)))
-                }
-                TypeDefinition::String(string_type) => {
-                    Some(Segment::Text(StringValidator::from(string_type)))
+            return match parameter.definition() {
+                Some(TypeDefinition::Array { items }) => {
+                    let format = parameter.collection_format.unwrap_or_default();
+                    let item = scalar(items.definition.as_ref());
+                    Some(Segment::List(format, Box::new(item)))
                 }
-                _ => Some(Segment::Text(StringValidator::default())),
+                definition => Some(scalar(definition)),
             };
         }
-        return None;
+        None
     }
-}
 
-impl<'a> From<(&'a String, &'a Operation)> for SegmentIter<'a> {
-    fn from(tuple: (&'a String, &'a Operation)) -> Self {
-        let (path, operation) = tuple;
-        let tokens: Vec<&str> = path[1..].split("/").collect();
+    /// Segments of `path`, typed by the path parameters in `parameters`.
+    pub fn new(path: &'a str, parameters: Vec<&'a Parameter>) -> Self {
         SegmentIter {
-            tokens,
-            parameters: &operation.parameters,
+            tokens: tokens(path).collect(),
+            parameters,
             token_index: 0,
         }
     }
 }
+
+/// Segments of a path template, typed by the parameters of one of its
+/// operations along with those shared by the whole path.
+impl<'a> From<(&'a String, &'a Operations, &'a Operation)> for SegmentIter<'a> {
+    fn from(tuple: (&'a String, &'a Operations, &'a Operation)) -> Self {
+        let (path, operations, operation) = tuple;
+        SegmentIter::new(path, operations.effective_parameters(operation))
+    }
+}
@@ -0,0 +1,369 @@
+use std::collections::HashMap;
+
+use derive_more::Display;
+use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use yaml_rust::Yaml;
+
+use super::uri::{pieces, segments, tokens, Piece};
+use super::{CollectionFormat, In, Method, Operation, Operations, Parameter, Paths};
+use definition::Definitions;
+use swagger::Swagger;
+use validator::common::to_validator;
+
+/// Characters left alone when encoding a path segment or query value.
+const ENCODE: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'.')
+    .remove(b'_')
+    .remove(b'~');
+
+#[derive(Debug, Clone, PartialEq, Display)]
+pub enum UrlError {
+    #[display(fmt = "No operation {}", _0)]
+    UnknownOperation(String),
+    #[display(fmt = "No {} operation at {}", "_0.as_str()", _1)]
+    UnknownRoute(Method, String),
+    /// A value given for a name the operation has no parameter for.
+    #[display(fmt = "Unknown parameter {}", _0)]
+    UnknownParameter(String),
+    #[display(fmt = "Missing required parameter {}", _0)]
+    MissingParameter(String),
+    #[display(fmt = "Invalid parameter {}: {}", _0, _1)]
+    InvalidParameter(String, String),
+}
+
+fn encode(text: &str) -> String {
+    utf8_percent_encode(text, ENCODE).to_string()
+}
+
+/// Writes a scalar as text, not yet encoded.
+fn scalar(name: &str, value: &Yaml) -> Result<String, UrlError> {
+    match value {
+        Yaml::Integer(number) => Ok(number.to_string()),
+        Yaml::Real(text) | Yaml::String(text) => Ok(text.clone()),
+        Yaml::Boolean(boolean) => Ok(boolean.to_string()),
+        _ => {
+            let message = "field cannot be written into a URL".to_string();
+            Err(UrlError::InvalidParameter(name.to_string(), message))
+        }
+    }
+}
+
+/// The items of an array value written as text, or the one of a scalar.
+fn items(name: &str, value: &Yaml) -> Result<Vec<String>, UrlError> {
+    match value {
+        Yaml::Array(items) => items.iter().map(|item| scalar(name, item)).collect(),
+        _ => Ok(vec![scalar(name, value)?]),
+    }
+}
+
+/// Writes the value of `parameter` as URL text, one item per repetition
+/// of the parameter. Arrays are joined by their `collectionFormat`, except
+/// `multi`, which repeats the parameter instead.
+fn texts(parameter: &Parameter, value: &Yaml) -> Result<Vec<String>, UrlError> {
+    let items = items(&parameter.name, value)?;
+    let texts: Vec<String> = items.iter().map(|item| encode(item)).collect();
+    let format = parameter.collection_format.unwrap_or_default();
+    if format == CollectionFormat::Multi {
+        return Ok(texts);
+    }
+    // Items are encoded already, so only the separator can be white space.
+    let text = format.join(&texts).replace(' ', "%20").replace('\t', "%09");
+    Ok(vec![text])
+}
+
+/// Builds request URLs for the operations of `Paths`, the reverse of
+/// `Router`.
+///
+/// Path parameters are substituted into the template and query parameters
+/// appended in the order they are declared, both percent-encoded. Each
+/// value is validated by the schema of its parameter, as an incoming
+/// request would be: path values are decoded by the same segments the
+/// `Router` matches with. A path parameter cannot repeat, so one with the
+/// `multi` collection format is rejected. A builder made from a `Swagger`
+/// follows parameter references to its top-level parameters; one made from
+/// `Paths` skips them.
+pub struct UrlBuilder<'a> {
+    paths: Option<&'a Paths>,
+    base_path: &'a str,
+    definitions: Option<&'a Definitions>,
+    parameters: Option<&'a HashMap<String, Parameter>>,
+}
+
+impl<'a> UrlBuilder<'a> {
+    pub fn new(paths: &'a Paths) -> Self {
+        UrlBuilder::with_base_path(paths, "/")
+    }
+
+    /// Prefixes every URL with `base_path`, such as `/v2`.
+    pub fn with_base_path(paths: &'a Paths, base_path: &'a str) -> Self {
+        UrlBuilder {
+            paths: Some(paths),
+            base_path,
+            definitions: None,
+            parameters: None,
+        }
+    }
+
+    /// The URL of the operation `operation_id`.
+    pub fn url_for(
+        &self, operation_id: &str, values: &HashMap<&str, Yaml>,
+    ) -> Result<String, UrlError> {
+        for (template, operations) in self.paths.iter().flat_map(|paths| paths.iter()) {
+            for (_, operation) in operations.iter() {
+                if operation.operation_id.as_deref() == Some(operation_id) {
+                    return self.build(template, operations, operation, values);
+                }
+            }
+        }
+        Err(UrlError::UnknownOperation(operation_id.to_string()))
+    }
+
+    /// The URL of the `method` operation at `template`.
+    pub fn url(
+        &self, method: Method, template: &str, values: &HashMap<&str, Yaml>,
+    ) -> Result<String, UrlError> {
+        let unknown = || UrlError::UnknownRoute(method, template.to_string());
+        let operations = self
+            .paths
+            .and_then(|p| p.get(template))
+            .ok_or_else(unknown)?;
+        match operations.iter().find(|(m, _)| *m == method) {
+            Some((_, operation)) => self.build(template, operations, operation, values),
+            None => Err(unknown()),
+        }
+    }
+
+    fn build(
+        &self, template: &str, operations: &Operations, operation: &Operation,
+        values: &HashMap<&str, Yaml>,
+    ) -> Result<String, UrlError> {
+        let parameters = operations.resolved_parameters(operation, self.parameters);
+        let mut names: Vec<&&str> = values.keys().collect();
+        names.sort();
+        for name in names {
+            if !parameters.iter().any(|parameter| parameter.name == *name) {
+                return Err(UrlError::UnknownParameter(name.to_string()));
+            }
+        }
+
+        let empty = Definitions::new();
+        let definitions = self.definitions.unwrap_or(&empty);
+        let segments = segments(template, parameters.clone());
+        let mut path_values = HashMap::new();
+        let mut query = Vec::new();
+        for parameter in parameters.iter() {
+            if parameter.in_ != In::Path && parameter.in_ != In::Query {
+                continue;
+            }
+            let name = parameter.name.as_str();
+            let value = match values.get(name) {
+                Some(value) => value,
+                None if parameter.required || parameter.in_ == In::Path => {
+                    return Err(UrlError::MissingParameter(name.to_string()))
+                }
+                None => continue,
+            };
+            if parameter.in_ == In::Path {
+                let format = parameter.collection_format.unwrap_or_default();
+                if format == CollectionFormat::Multi {
+                    let message = "field cannot repeat in a path".to_string();
+                    return Err(UrlError::InvalidParameter(name.to_string(), message));
+                }
+                // The text as the router sees it once decoded, and as written.
+                let text = format.join(&items(name, value)?);
+                path_values.insert(name, (text, texts(parameter, value)?.remove(0)));
+                continue;
+            }
+            if let Some(message) =
+                to_validator(parameter.value_schema(), definitions).validate(value)
+            {
+                return Err(UrlError::InvalidParameter(name.to_string(), message));
+            }
+            let texts = texts(parameter, value)?;
+            query.extend(texts.into_iter().map(|text| (encode(name), text)));
+        }
+
+        let mut url: String = tokens(self.base_path).map(|t| format!("/{}", t)).collect();
+        for (token, segment) in tokens(template).zip(segments.iter()) {
+            url.push('/');
+            for piece in pieces(token) {
+                match piece {
+                    Piece::Literal(literal) => url.push_str(literal),
+                    Piece::Placeholder(name) => {
+                        let missing = || UrlError::MissingParameter(name.to_string());
+                        let (text, encoded) = path_values.get(name).ok_or_else(missing)?;
+                        if let Some(Err(message)) =
+                            segment.as_ref().map(|s| s.decode_part(name, text))
+                        {
+                            return Err(UrlError::InvalidParameter(name.to_string(), message));
+                        }
+                        url.push_str(encoded);
+                    }
+                }
+            }
+        }
+        if url.is_empty() {
+            url.push('/');
+        }
+        if !query.is_empty() {
+            let pairs: Vec<String> = query.iter().map(|(n, v)| format!("{}={}", n, v)).collect();
+            url.push('?');
+            url.push_str(&pairs.join("&"));
+        }
+        Ok(url)
+    }
+}
+
+impl<'a> From<&'a Paths> for UrlBuilder<'a> {
+    fn from(paths: &'a Paths) -> Self {
+        UrlBuilder::new(paths)
+    }
+}
+
+/// Builds URLs for the paths of `swagger` under its `basePath`, resolving
+/// parameter references against its parameters and references in their
+/// schemas against its definitions.
+impl<'a> From<&'a Swagger> for UrlBuilder<'a> {
+    fn from(swagger: &'a Swagger) -> Self {
+        UrlBuilder {
+            paths: swagger.paths.as_ref(),
+            base_path: swagger.base_path.as_deref().unwrap_or("/"),
+            definitions: swagger.definitions.as_ref(),
+            parameters: swagger.parameters.as_ref(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate yaml_rust;
+
+    use std::path::PathBuf;
+
+    use yaml_rust::Yaml;
+
+    use super::{UrlBuilder, UrlError};
+    use path::{Method, Paths};
+    use swagger::Swagger;
+
+    fn strings(items: &[&str]) -> Yaml {
+        Yaml::Array(items.iter().map(|s| Yaml::String(s.to_string())).collect())
+    }
+
+    #[test]
+    fn test_url_for() {
+        let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        path.push("src/swagger/test.yaml");
+        let swagger = Swagger::from_path(&path).unwrap();
+        let builder = UrlBuilder::from(&swagger);
+
+        let values = hashmap!("petId" => Yaml::Integer(10));
+        assert_eq!(
+            builder.url_for("getPetById", &values).unwrap(),
+            "/v2/pet/10"
+        );
+        let values = hashmap!("username" => Yaml::String("john doe/2".into()));
+        let url = builder.url(Method::Get, "/user/{username}", &values);
+        assert_eq!(url.unwrap(), "/v2/user/john%20doe%2F2");
+        let values = hashmap!("status" => strings(&["available", "sold"]));
+        assert_eq!(
+            builder.url_for("findPetsByStatus", &values).unwrap(),
+            "/v2/pet/findByStatus?status=available&status=sold"
+        );
+
+        let values = hashmap!("status" => strings(&["lost"]));
+        match builder.url_for("findPetsByStatus", &values) {
+            Err(UrlError::InvalidParameter(name, _)) => assert_eq!(name, "status"),
+            _ => panic!("Not matched"),
+        }
+        let values = hashmap!("orderId" => Yaml::Integer(50));
+        match builder.url_for("getOrderById", &values) {
+            Err(error) => assert_eq!(
+                error.to_string(),
+                "Invalid parameter orderId: field is too large"
+            ),
+            _ => panic!("Not matched"),
+        }
+        match builder.url_for("getPetById", &hashmap!()) {
+            Err(error) => assert_eq!(error, UrlError::MissingParameter("petId".into())),
+            _ => panic!("Not matched"),
+        }
+        let values = hashmap!("petId" => Yaml::Integer(10), "owner" => Yaml::Integer(1));
+        match builder.url_for("getPetById", &values) {
+            Err(error) => assert_eq!(error, UrlError::UnknownParameter("owner".into())),
+            _ => panic!("Not matched"),
+        }
+        match builder.url_for("adoptPet", &hashmap!()) {
+            Err(error) => assert_eq!(error, UrlError::UnknownOperation("adoptPet".into())),
+            _ => panic!("Not matched"),
+        }
+    }
+
+    #[test]
+    fn test_collection_format() {
+        let s = "
+        /items/{ids}:
+          get:
+            parameters:
+              - name: ids
+                in: path
+                required: true
+                type: array
+                items:
+                  type: integer
+              - name: tags
+                in: query
+                type: array
+                items:
+                  type: string
+                collectionFormat: pipes
+              - name: words
+                in: query
+                type: array
+                items:
+                  type: string
+                collectionFormat: ssv
+              - name: limit
+                in: query
+                type: integer
+        /tags/{tags}:
+          get:
+            parameters:
+              - name: tags
+                in: path
+                required: true
+                type: array
+                items:
+                  type: string
+                collectionFormat: multi
+        ";
+        let paths: Paths = serde_yaml::from_str(s).unwrap();
+        let builder = UrlBuilder::new(&paths);
+        let values = hashmap!(
+            "ids" => Yaml::Array(vec![Yaml::Integer(1), Yaml::Integer(2)]),
+            "tags" => strings(&["a", "b&c"]),
+            "words" => strings(&["x", "y"]),
+        );
+        let url = builder.url(Method::Get, "/items/{ids}", &values).unwrap();
+        assert_eq!(url, "/items/1,2?tags=a|b%26c&words=x%20y");
+        match builder.url(Method::Post, "/items/{ids}", &values) {
+            Err(error) => assert_eq!(error.to_string(), "No post operation at /items/{ids}"),
+            _ => panic!("Not matched"),
+        }
+
+        let values = hashmap!("ids" => strings(&["1", "x"]));
+        match builder.url(Method::Get, "/items/{ids}", &values) {
+            Err(error) => assert_eq!(
+                error.to_string(),
+                "Invalid parameter ids: field is not integer"
+            ),
+            _ => panic!("Not matched"),
+        }
+        let values = hashmap!("tags" => strings(&["a", "b"]));
+        match builder.url(Method::Get, "/tags/{tags}", &values) {
+            Err(UrlError::InvalidParameter(name, _)) => assert_eq!(name, "tags"),
+            _ => panic!("Not matched"),
+        }
+    }
+}
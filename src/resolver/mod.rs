@@ -0,0 +1,266 @@
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+use derive_more::Display;
+use serde_yaml::Value;
+use yaml_rust::Yaml;
+
+use common::Attribute;
+use validator::common::to_validator;
+use validator::reference::{file_reference, Location, UnresolvedValidator, ValidatorQuerier};
+use validator::Validator;
+
+#[derive(Debug, Clone, PartialEq, Display)]
+pub enum ResolveError {
+    #[display(fmt = "Cannot load {}: {}", _0, _1)]
+    File(String, String),
+    #[display(fmt = "No such pointer {}#{}", _0, _1)]
+    Pointer(String, String),
+    #[display(fmt = "Invalid schema at {}#{}: {}", _0, _1, _2)]
+    Schema(String, String, String),
+    #[display(fmt = "Circular reference at {}#{}", _0, _1)]
+    Circular(String, String),
+    #[display(fmt = "Unsupported reference")]
+    Unsupported,
+}
+
+/// Resolves `$ref`s across files on the local filesystem.
+///
+/// Relative file references are taken relative to the document containing
+/// them. Each file is loaded once, with every `$ref` inside rewritten to a
+/// `file://` URI so that it can be resolved without knowing where it came
+/// from.
+pub struct Resolver {
+    root: PathBuf,
+    documents: RefCell<HashMap<PathBuf, Rc<Value>>>,
+}
+
+impl Resolver {
+    pub fn new<P: AsRef<Path>>(root: P) -> Resolver {
+        Resolver {
+            root: root.as_ref().to_path_buf(),
+            documents: RefCell::new(HashMap::new()),
+        }
+    }
+
+    fn load(&self, path: &Path) -> Result<(PathBuf, Rc<Value>), ResolveError> {
+        let to_error =
+            |e: &dyn ToString| ResolveError::File(path.display().to_string(), e.to_string());
+        let path = path.canonicalize().map_err(|e| to_error(&e))?;
+        if let Some(document) = self.documents.borrow().get(&path) {
+            return Ok((path, document.clone()));
+        }
+        let file = File::open(&path).map_err(|e| to_error(&e))?;
+        let mut document: Value = serde_yaml::from_reader(file).map_err(|e| to_error(&e))?;
+        absolutize(&mut document, &path);
+        let document = Rc::new(document);
+        self.documents
+            .borrow_mut()
+            .insert(path.clone(), document.clone());
+        Ok((path, document))
+    }
+
+    fn locate(&self, location: &Location) -> Result<(PathBuf, String), ResolveError> {
+        match location {
+            Location::Local(name) => Ok((self.root.clone(), format!("/definitions/{}", name))),
+            Location::Component(name) => {
+                Ok((self.root.clone(), format!("/components/schemas/{}", name)))
+            }
+            Location::Parameter(name) => Ok((self.root.clone(), format!("/parameters/{}", name))),
+            Location::Response(name) => Ok((self.root.clone(), format!("/responses/{}", name))),
+            Location::Remote(file, pointer) => {
+                let directory = self.root.parent().unwrap_or_else(|| Path::new(""));
+                Ok((directory.join(file), pointer.clone()))
+            }
+            Location::Unknown => Err(ResolveError::Unsupported),
+        }
+    }
+
+    /// Looks up the schema at `location` without following any reference it
+    /// holds, along with the canonical file and the pointer it was found at.
+    pub fn fetch(&self, location: &Location) -> Result<(PathBuf, String, Attribute), ResolveError> {
+        let (path, pointer) = self.locate(location)?;
+        let (path, document) = self.load(&path)?;
+        let file = path.display().to_string();
+        let value = match lookup(&document, &pointer) {
+            Some(value) => value,
+            None => return Err(ResolveError::Pointer(file, pointer)),
+        };
+        match serde_yaml::from_value(value.clone()) {
+            Ok(attribute) => Ok((path, pointer, attribute)),
+            Err(e) => Err(ResolveError::Schema(file, pointer, e.to_string())),
+        }
+    }
+
+    /// Follows `location` through any chain of references to the schema it
+    /// finally points at.
+    pub fn resolve(&self, location: &Location) -> Result<Attribute, ResolveError> {
+        let mut visited: HashSet<(PathBuf, String)> = HashSet::new();
+        let mut location = location.clone();
+        loop {
+            let (path, pointer, attribute) = self.fetch(&location)?;
+            if visited.contains(&(path.clone(), pointer.clone())) {
+                return Err(ResolveError::Circular(path.display().to_string(), pointer));
+            }
+            visited.insert((path, pointer));
+            match &attribute.reference {
+                Some(reference) => location = Location::from(reference.to_string()),
+                None => return Ok(attribute),
+            }
+        }
+    }
+
+    /// The root spec file, canonicalized.
+    pub fn root(&self) -> Result<PathBuf, ResolveError> {
+        let to_error =
+            |e: io::Error| ResolveError::File(self.root.display().to_string(), e.to_string());
+        self.root.canonicalize().map_err(to_error)
+    }
+}
+
+impl ValidatorQuerier for Resolver {
+    fn get(&self, location: &Location) -> Option<Box<dyn Validator<Yaml> + '_>> {
+        match self.resolve(location) {
+            Ok(attribute) => Some(to_validator(&attribute, self)),
+            Err(e) => Some(Box::new(UnresolvedValidator {
+                reason: e.to_string(),
+            })),
+        }
+    }
+}
+
+/// Escapes `token` for use in a JSON pointer, e.g. `/pet` as `~1pet`.
+pub fn escape(token: &str) -> String {
+    token.replace('~', "~0").replace('/', "~1")
+}
+
+/// Reverses `escape`, e.g. `~1pet` to `/pet`.
+pub fn unescape(token: &str) -> String {
+    token.replace("~1", "/").replace("~0", "~")
+}
+
+/// Looks up a JSON pointer such as `/definitions/Pet/properties/id`.
+pub fn lookup<'a>(document: &'a Value, pointer: &str) -> Option<&'a Value> {
+    if pointer.is_empty() {
+        return Some(document);
+    }
+    if !pointer.starts_with('/') {
+        return None;
+    }
+    let mut value = document;
+    for token in pointer[1..].split('/') {
+        let token = unescape(token);
+        value = match value {
+            Value::Mapping(mapping) => mapping.get(&Value::String(token))?,
+            Value::Sequence(sequence) => sequence.get(token.parse::<usize>().ok()?)?,
+            _ => return None,
+        };
+    }
+    Some(value)
+}
+
+fn absolutize(value: &mut Value, path: &Path) {
+    match value {
+        Value::Mapping(mapping) => {
+            let key = Value::String("$ref".to_string());
+            if let Some(Value::String(reference)) = mapping.get_mut(&key) {
+                let mut splitted = reference.splitn(2, '#');
+                let file = splitted.next().unwrap_or("");
+                let pointer = splitted.next().unwrap_or("");
+                let file = match file.is_empty() {
+                    true => path.to_path_buf(),
+                    false => path.parent().unwrap_or_else(|| Path::new("")).join(file),
+                };
+                *reference = file_reference(&file, pointer);
+            }
+            for (_, v) in mapping.iter_mut() {
+                absolutize(v, path);
+            }
+        }
+        Value::Sequence(sequence) => {
+            for v in sequence.iter_mut() {
+                absolutize(v, path);
+            }
+        }
+        _ => (),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate yaml_rust;
+
+    use std::path::PathBuf;
+
+    use yaml_rust::YamlLoader;
+
+    use super::{ResolveError, Resolver};
+    use validator::reference::{Location, ReferenceValidator};
+    use validator::Validator;
+
+    fn resolver() -> Resolver {
+        let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        path.push("src/resolver/test.yaml");
+        Resolver::new(path)
+    }
+
+    #[test]
+    fn test_resolve() {
+        let resolver = resolver();
+
+        let location = Location::from("#/definitions/Pet".to_string());
+        assert!(resolver.resolve(&location).is_ok());
+        let location = Location::from("common.yaml#/definitions/Error/properties/code".to_string());
+        assert!(resolver.resolve(&location).unwrap().definition.is_some());
+
+        let location = Location::from("missing.yaml#/definitions/Error".to_string());
+        match resolver.resolve(&location) {
+            Err(ResolveError::File(file, _)) => assert!(file.ends_with("missing.yaml")),
+            _ => panic!("Not matched"),
+        }
+        let location = Location::from("common.yaml#/definitions/Missing".to_string());
+        match resolver.resolve(&location) {
+            Err(ResolveError::Pointer(_, pointer)) => assert_eq!(pointer, "/definitions/Missing"),
+            _ => panic!("Not matched"),
+        }
+
+        let location = Location::from("#/definitions/Ping".to_string());
+        match resolver.resolve(&location) {
+            Err(ResolveError::Circular(file, pointer)) => {
+                assert!(file.ends_with("test.yaml"));
+                assert_eq!(pointer, "/definitions/Ping");
+            }
+            _ => panic!("Not matched"),
+        }
+    }
+
+    #[test]
+    fn test_validate_remote() {
+        let resolver = resolver();
+        let location = Location::from("#/definitions/Pet".to_string());
+        let v = ReferenceValidator::new(location, &resolver);
+
+        let s = "
+        name: doggie
+        error:
+          code: 1
+          message: ok
+        ";
+        let docs = YamlLoader::load_from_str(s).unwrap();
+        let doc = &docs[0];
+        assert_eq!(v.validate(doc), None);
+
+        let docs = YamlLoader::load_from_str("{name: doggie, error: {code: a}}").unwrap();
+        let doc = &docs[0];
+        assert_eq!(v.validate(doc), some_str!("field is not integer"));
+
+        let docs = YamlLoader::load_from_str("{name: doggie, owner: {}}").unwrap();
+        let doc = &docs[0];
+        let error = v.validate(doc).unwrap();
+        assert!(error.starts_with("No such pointer") && error.ends_with("#/definitions/Owner"));
+    }
+}
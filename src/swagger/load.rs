@@ -0,0 +1,352 @@
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+use std::str::FromStr;
+
+use derive_more::Display;
+use serde::de::DeserializeOwned;
+use serde_yaml::Value;
+
+use super::Swagger;
+use common::Referable;
+use definition::{unsatisfiable, Definitions};
+use path::{In, Parameter};
+
+#[derive(Debug, Clone, PartialEq, Display)]
+pub enum LoadError {
+    #[display(fmt = "Cannot read {}: {}", _0, _1)]
+    Io(String, String),
+    #[display(
+        fmt = "Malformed document at line {}, column {}: {}",
+        line,
+        column,
+        message
+    )]
+    Syntax {
+        line: usize,
+        column: usize,
+        message: String,
+    },
+    #[display(fmt = "Unsupported document version {}", _0)]
+    Version(String),
+    #[display(fmt = "Missing document version")]
+    MissingVersion,
+    #[display(fmt = "Invalid document at {}: {}", path, message)]
+    Invalid { path: String, message: String },
+    #[display(fmt = "Definitions can never be satisfied: {}", "_0.join(\", \")")]
+    Unsatisfiable(Vec<String>),
+}
+
+pub(crate) fn parse(s: &str) -> Result<Value, LoadError> {
+    if s.trim_start().starts_with('{') {
+        return serde_json::from_str(s).map_err(|e| LoadError::Syntax {
+            line: e.line(),
+            column: e.column(),
+            message: e.to_string(),
+        });
+    }
+    serde_yaml::from_str(s).map_err(|e| {
+        let (line, column) = match e.location() {
+            Some(location) => (location.line(), location.column()),
+            None => (0, 0),
+        };
+        LoadError::Syntax {
+            line,
+            column,
+            message: e.to_string(),
+        }
+    })
+}
+
+/// Checks the version string under `key` against `supported`.
+pub(crate) fn check_version<F>(document: &Value, key: &str, supported: F) -> Result<(), LoadError>
+where
+    F: Fn(&str) -> bool,
+{
+    let key = Value::String(key.to_string());
+    let version = document.as_mapping().and_then(|mapping| mapping.get(&key));
+    match version {
+        Some(Value::String(version)) if supported(version) => Ok(()),
+        Some(Value::String(version)) => Err(LoadError::Version(version.clone())),
+        Some(value) => Err(LoadError::Version(format!("{:?}", value))),
+        None => Err(LoadError::MissingVersion),
+    }
+}
+
+/// Deserializes a parsed document, locating any error by its key path.
+pub(crate) fn deserialize<T: DeserializeOwned>(document: Value) -> Result<T, LoadError> {
+    serde_path_to_error::deserialize(document).map_err(|e| LoadError::Invalid {
+        path: e.path().to_string(),
+        message: e.inner().to_string(),
+    })
+}
+
+pub(crate) fn check_satisfiable(definitions: &Option<Definitions>) -> Result<(), LoadError> {
+    if let Some(definitions) = definitions {
+        let names = unsatisfiable(definitions);
+        if !names.is_empty() {
+            let names = names.into_iter().map(String::from).collect();
+            return Err(LoadError::Unsatisfiable(names));
+        }
+    }
+    Ok(())
+}
+
+/// Rejects parameters in a location Swagger 2.0 lacks, such as `cookie`,
+/// which the shared parameter model accepts for OpenAPI 3.0.
+fn check_locations(swagger: &Swagger) -> Result<(), LoadError> {
+    let unsupported = |path: String, parameter: &Parameter| match parameter.in_ {
+        In::Cookie => Err(LoadError::Invalid {
+            path: format!("{}.in", path),
+            message: "cookie parameters are not supported in Swagger 2.0".to_string(),
+        }),
+        _ => Ok(()),
+    };
+    if let Some(parameters) = &swagger.parameters {
+        let mut names: Vec<&String> = parameters.keys().collect();
+        names.sort();
+        for name in names {
+            unsupported(format!("parameters.{}", name), &parameters[name])?;
+        }
+    }
+    let check_list = |path: String, list: &Option<Vec<Referable<Parameter>>>| {
+        for (index, parameter) in list.iter().flatten().enumerate() {
+            if let Referable::Inline(parameter) = parameter {
+                unsupported(format!("{}.parameters[{}]", path, index), parameter)?;
+            }
+        }
+        Ok(())
+    };
+    for (path, operations) in swagger.paths.iter().flatten() {
+        check_list(format!("paths.{}", path), &operations.parameters)?;
+        for (method, operation) in operations.iter() {
+            let path = format!("paths.{}.{}", path, method.as_str());
+            check_list(path, &operation.parameters)?;
+        }
+    }
+    Ok(())
+}
+
+pub(crate) fn read<R: Read>(mut reader: R) -> Result<String, LoadError> {
+    let mut s = String::new();
+    match reader.read_to_string(&mut s) {
+        Ok(_) => Ok(s),
+        Err(e) => Err(LoadError::Io("input".to_string(), e.to_string())),
+    }
+}
+
+pub(crate) fn read_path(path: &Path) -> Result<String, LoadError> {
+    let to_error = |e: std::io::Error| LoadError::Io(path.display().to_string(), e.to_string());
+    let mut s = String::new();
+    File::open(path)
+        .and_then(|mut file| file.read_to_string(&mut s))
+        .map_err(to_error)?;
+    Ok(s)
+}
+
+impl FromStr for Swagger {
+    type Err = LoadError;
+
+    /// Parses a Swagger 2.0 document, written either as JSON or as YAML,
+    /// rejecting definitions that can never be satisfied.
+    fn from_str(s: &str) -> Result<Swagger, LoadError> {
+        let swagger = Swagger::from_str_lenient(s)?;
+        check_satisfiable(&swagger.definitions)?;
+        Ok(swagger)
+    }
+}
+
+impl Swagger {
+    /// Parses a document like `from_str`, but keeps definitions that can
+    /// never be satisfied.
+    pub fn from_str_lenient(s: &str) -> Result<Swagger, LoadError> {
+        let document = parse(s)?;
+        check_version(&document, "swagger", |version| version == "2.0")?;
+        let swagger = deserialize(document)?;
+        check_locations(&swagger)?;
+        Ok(swagger)
+    }
+
+    pub fn from_reader<R: Read>(reader: R) -> Result<Swagger, LoadError> {
+        read(reader)?.parse()
+    }
+
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Swagger, LoadError> {
+        read_path(path.as_ref())?.parse()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::LoadError;
+    use common::Referable;
+    use swagger::Swagger;
+
+    #[test]
+    fn test_from_path() {
+        let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        path.push("src/swagger/test.yaml");
+        let root = Swagger::from_path(&path).unwrap();
+        assert_eq!(root.spec.unwrap(), "2.0");
+
+        path.set_file_name("missing.yaml");
+        match Swagger::from_path(&path) {
+            Err(LoadError::Io(file, _)) => assert!(file.ends_with("missing.yaml")),
+            _ => panic!("Not matched"),
+        }
+    }
+
+    #[test]
+    fn test_from_json() {
+        let s = r#"{"swagger": "2.0", "paths": {"/pet": {"get": {"operationId": "listPets"}}}}"#;
+        let root: Swagger = s.parse().unwrap();
+        let operations = root.paths.as_ref().unwrap().get("/pet").unwrap();
+        let operation = operations.get.as_ref().unwrap();
+        assert_eq!(operation.operation_id.as_ref().unwrap(), "listPets");
+        let root = Swagger::from_reader(s.as_bytes()).unwrap();
+        assert!(root.paths.is_some());
+    }
+
+    #[test]
+    fn test_errors() {
+        let error = "swagger: '2.0'\nhost: a\n- b\nbasePath: /"
+            .parse::<Swagger>()
+            .unwrap_err();
+        match error {
+            LoadError::Syntax { line, .. } => assert_eq!(line, 3),
+            _ => panic!("Not matched"),
+        }
+
+        let error = "{\"swagger\": \"2.0\",}".parse::<Swagger>().unwrap_err();
+        match error {
+            LoadError::Syntax { line, .. } => assert_eq!(line, 1),
+            _ => panic!("Not matched"),
+        }
+
+        let error = "swagger: '3.0'".parse::<Swagger>().unwrap_err();
+        assert_eq!(error, LoadError::Version("3.0".into()));
+        let error = "info: {}".parse::<Swagger>().unwrap_err();
+        assert_eq!(error, LoadError::MissingVersion);
+
+        let s = "
+        swagger: '2.0'
+        schemes:
+          - https
+          - gopher
+        ";
+        match s.parse::<Swagger>().unwrap_err() {
+            LoadError::Invalid { path, .. } => assert_eq!(path, "schemes[1]"),
+            _ => panic!("Not matched"),
+        }
+
+        let s = "
+        swagger: '2.0'
+        paths:
+          /pet:
+            get:
+              parameters:
+                - name: limit
+                  in: query
+                  type: integer
+                  minimum: abc
+        ";
+        match s.parse::<Swagger>().unwrap_err() {
+            LoadError::Invalid { path, message } => {
+                assert_eq!(path, "paths./pet.get.parameters[0]");
+                assert_eq!(
+                    message,
+                    "minimum: invalid type: string \"abc\", expected i64"
+                );
+            }
+            _ => panic!("Not matched"),
+        }
+        let s = "
+        swagger: '2.0'
+        definitions:
+          Pet:
+            type: object
+            properties:
+              id:
+                type: integer
+                maximum: lots
+        ";
+        match s.parse::<Swagger>().unwrap_err() {
+            LoadError::Invalid { path, message } => {
+                assert_eq!(path, "definitions.Pet");
+                assert!(message.starts_with("properties.id: maximum: invalid type"));
+            }
+            _ => panic!("Not matched"),
+        }
+
+        let s = "
+        swagger: '2.0'
+        paths:
+          /pet:
+            get:
+              parameters:
+                - name: limit
+                  type: integer
+        ";
+        match s.parse::<Swagger>().unwrap_err() {
+            LoadError::Invalid { message, .. } => assert!(message.contains("missing field `in`")),
+            _ => panic!("Not matched"),
+        }
+
+        let s = "
+        swagger: '2.0'
+        paths:
+          /pet:
+            get:
+              parameters:
+                - name: session
+                  in: cookie
+                  type: string
+        ";
+        match s.parse::<Swagger>().unwrap_err() {
+            LoadError::Invalid { path, message } => {
+                assert_eq!(path, "paths./pet.get.parameters[0].in");
+                assert_eq!(
+                    message,
+                    "cookie parameters are not supported in Swagger 2.0"
+                );
+            }
+            _ => panic!("Not matched"),
+        }
+
+        let s = "
+        swagger: '2.0'
+        paths:
+          /pet:
+            get:
+              parameters:
+                - $ref: '#/parameters/limit'
+        ";
+        let root = s.parse::<Swagger>().unwrap();
+        let operation = root.paths.as_ref().unwrap()["/pet"].get.as_ref().unwrap();
+        match &operation.parameters.as_ref().unwrap()[0] {
+            Referable::Reference { reference } => assert_eq!(reference, "#/parameters/limit"),
+            _ => panic!("Not matched"),
+        }
+
+        let s = "
+        swagger: '2.0'
+        definitions:
+          Loop:
+            type: object
+            required:
+              - next
+            properties:
+              next:
+                $ref: '#/definitions/Loop'
+        ";
+        let error = s.parse::<Swagger>().unwrap_err();
+        assert_eq!(error, LoadError::Unsatisfiable(vec!["Loop".into()]));
+        assert!(Swagger::from_str_lenient(s).is_ok());
+        assert_eq!(
+            error.to_string(),
+            "Definitions can never be satisfied: Loop"
+        );
+    }
+}
@@ -1,19 +1,242 @@
+use std::collections::HashMap;
+
+use common::{resolve_references, unmodeled, Attribute, Extensions, Extra, Referable};
 use definition::Definitions;
-use path::Paths;
+use path::{Operation, Operations, Parameter, Paths, Response};
+use resolver::ResolveError;
+use validator::reference::Location;
+
+pub mod load;
+pub mod security;
+
+use self::security::{SecurityDefinitions, SecurityRequirement};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Contact {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+    #[serde(flatten)]
+    pub extensions: Extensions,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct License {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(flatten)]
+    pub extensions: Extensions,
+}
 
-#[derive(Debug, PartialEq, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Info {
+    pub title: String,
+    pub version: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub terms_of_service: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub contact: Option<Contact>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub license: Option<License>,
+    #[serde(flatten)]
+    pub extensions: Extensions,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExternalDocs {
+    pub url: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(flatten)]
+    pub extensions: Extensions,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Tag {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub external_docs: Option<ExternalDocs>,
+    #[serde(flatten)]
+    pub extensions: Extensions,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Scheme {
+    Http,
+    Https,
+    Ws,
+    Wss,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct Swagger {
-    #[serde(rename = "swagger")]
+    #[serde(rename = "swagger", skip_serializing_if = "Option::is_none")]
     pub spec: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub info: Option<Info>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub host: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub base_path: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub schemes: Option<Vec<Scheme>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub consumes: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub produces: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tags: Option<Vec<Tag>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub security_definitions: Option<SecurityDefinitions>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub security: Option<Vec<SecurityRequirement>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub external_docs: Option<ExternalDocs>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub definitions: Option<Definitions>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parameters: Option<HashMap<String, Parameter>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub responses: Option<HashMap<String, Response>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub paths: Option<Paths>,
+    #[serde(flatten)]
+    pub extensions: Extensions,
+    #[serde(flatten, deserialize_with = "unmodeled")]
+    pub extra: Extra,
+}
+
+fn inline<T>(referable: &mut Referable<T>) -> Option<&mut T> {
+    match referable {
+        Referable::Inline(value) => Some(value),
+        _ => None,
+    }
+}
+
+impl Swagger {
+    /// Looks up a parameter reference such as `#/parameters/limit`.
+    pub fn parameter(&self, reference: &str) -> Result<&Parameter, ResolveError> {
+        let name = match Location::from(reference.to_string()) {
+            Location::Parameter(name) => name,
+            _ => return Err(ResolveError::Unsupported),
+        };
+        let parameters = self.parameters.as_ref();
+        match parameters.and_then(|parameters| parameters.get(&name)) {
+            Some(parameter) => Ok(parameter),
+            None => Err(ResolveError::Pointer(
+                String::new(),
+                format!("/parameters/{}", name),
+            )),
+        }
+    }
+
+    /// Looks up a response reference such as `#/responses/NotFound`.
+    pub fn response(&self, reference: &str) -> Result<&Response, ResolveError> {
+        let name = match Location::from(reference.to_string()) {
+            Location::Response(name) => name,
+            _ => return Err(ResolveError::Unsupported),
+        };
+        let responses = self.responses.as_ref();
+        match responses.and_then(|responses| responses.get(&name)) {
+            Some(response) => Ok(response),
+            None => Err(ResolveError::Pointer(
+                String::new(),
+                format!("/responses/{}", name),
+            )),
+        }
+    }
+
+    /// The parameters `operation` at `operations` takes, with references to
+    /// top-level parameters followed. See `Operations::resolved_parameters`.
+    pub fn effective_parameters<'a>(
+        &'a self, operations: &'a Operations, operation: &'a Operation,
+    ) -> Vec<&'a Parameter> {
+        operations.resolved_parameters(operation, self.parameters.as_ref())
+    }
+
+    /// Every top-level schema in the document: definitions, and the schemas
+    /// of parameters, responses and response headers. Nested schemas are
+    /// reached through `Attribute::children_mut`.
+    pub fn schemas_mut(&mut self) -> Vec<&mut Attribute> {
+        let mut schemas: Vec<&mut Attribute> = Vec::new();
+        schemas.extend(self.definitions.iter_mut().flat_map(|d| d.values_mut()));
+        let mut parameters: Vec<&mut Parameter> = Vec::new();
+        let mut responses: Vec<&mut Response> = Vec::new();
+        parameters.extend(self.parameters.iter_mut().flat_map(|p| p.values_mut()));
+        responses.extend(self.responses.iter_mut().flat_map(|r| r.values_mut()));
+        for operations in self.paths.iter_mut().flat_map(|paths| paths.values_mut()) {
+            let (shared, operations) = operations.split_mut();
+            parameters.extend(shared.into_iter().flatten().filter_map(inline));
+            for operation in operations {
+                let own = operation.parameters.iter_mut().flatten();
+                parameters.extend(own.filter_map(inline));
+                let own = operation.responses.iter_mut().flat_map(|r| r.values_mut());
+                responses.extend(own.filter_map(inline));
+            }
+        }
+        for parameter in parameters {
+            schemas.extend(parameter.schema.iter_mut());
+            schemas.push(&mut parameter.attribute);
+        }
+        for response in responses {
+            schemas.extend(response.schema.iter_mut());
+            schemas.extend(response.headers.iter_mut().flat_map(|h| h.values_mut()));
+        }
+        schemas
+    }
+
+    /// Replaces every parameter reference in `paths` with a copy of the
+    /// top-level parameter it points at.
+    pub fn resolve_parameters(&mut self) -> Result<(), ResolveError> {
+        let mut paths = match self.paths.take() {
+            Some(paths) => paths,
+            None => return Ok(()),
+        };
+        let lists = paths.values_mut().flat_map(|o| o.parameter_lists_mut());
+        let result = resolve_references(lists.flat_map(|l| l.iter_mut()), |r| self.parameter(r));
+        self.paths = Some(paths);
+        result
+    }
+
+    /// Replaces every response reference in `paths` with a copy of the
+    /// top-level response it points at.
+    pub fn resolve_responses(&mut self) -> Result<(), ResolveError> {
+        let mut paths = match self.paths.take() {
+            Some(paths) => paths,
+            None => return Ok(()),
+        };
+        let operations = paths.values_mut().flat_map(|o| o.iter_mut());
+        let responses =
+            operations.flat_map(|o| o.responses.iter_mut().flat_map(|r| r.values_mut()));
+        let result = resolve_references(responses, |r| self.response(r));
+        self.paths = Some(paths);
+        result
+    }
 }
 
 #[cfg(test)]
+#[allow(clippy::len_zero)]
 mod tests {
     use std::fs::File;
     use std::path::PathBuf;
-    use swagger::Swagger;
+
+    use common::Referable;
+    use path::uri::{Segment, SegmentIter};
+    use resolver::ResolveError;
+    use swagger::security::{Flow, SecurityScheme};
+    use swagger::{Scheme, Swagger};
 
     #[test]
     fn load_test_yaml() {
@@ -21,7 +244,214 @@ mod tests {
         path.push("src/swagger/test.yaml");
         let file = File::open(path.to_str().unwrap()).unwrap();
         let root: Swagger = serde_yaml::from_reader(&file).unwrap();
-        assert!(root.paths.unwrap().len() > 0);
+        assert!(root.paths.as_ref().unwrap().len() > 0);
         assert!(root.definitions.unwrap().len() > 0);
+        assert_eq!(root.info.unwrap().title, "Swagger Petstore");
+        assert_eq!(root.base_path.unwrap(), "/v2");
+        assert_eq!(root.schemes.unwrap(), vec![Scheme::Https, Scheme::Http]);
+        assert_eq!(root.tags.unwrap().len(), 3);
+        assert!(root.extra.is_empty());
+
+        let paths = root.paths.as_ref().unwrap();
+        let mut operations = paths.values().flat_map(|operations| operations.iter());
+        assert!(operations.all(|(_, operation)| operation.extra.is_empty()));
+        let pet = paths.get("/pet/{petId}").unwrap();
+        let get_pet = pet.get.as_ref().unwrap();
+        assert_eq!(get_pet.operation_id.as_ref().unwrap(), "getPetById");
+        match get_pet.response(200).unwrap() {
+            Referable::Inline(response) => assert!(response.schema.is_some()),
+            _ => panic!("Not matched"),
+        }
+        assert!(get_pet.response(500).is_none());
+
+        let security_definitions = root.security_definitions.unwrap();
+        match security_definitions.get("petstore_auth").unwrap() {
+            SecurityScheme::OAuth2 { flow, scopes, .. } => {
+                assert_eq!(*flow, Flow::Implicit);
+                assert_eq!(scopes.len(), 2);
+            }
+            _ => panic!("Not matched"),
+        }
+    }
+
+    #[test]
+    fn test_resolve_parameters() {
+        let s = "
+        swagger: '2.0'
+        parameters:
+          petId:
+            name: petId
+            in: path
+            required: true
+            type: integer
+        responses:
+          NotFound:
+            description: Entity not found
+        paths:
+          /pet/{petId}:
+            get:
+              parameters:
+                - $ref: '#/parameters/petId'
+              responses:
+                '404':
+                  $ref: '#/responses/NotFound'
+        ";
+        let mut root: Swagger = serde_yaml::from_str(s).unwrap();
+        assert_eq!(
+            root.response("#/responses/NotFound").unwrap().description,
+            "Entity not found"
+        );
+        root.resolve_parameters().unwrap();
+        root.resolve_responses().unwrap();
+
+        let paths = root.paths.as_ref().unwrap();
+        let (uri, operations) = paths.iter().next().unwrap();
+        let operation = operations.get.as_ref().unwrap();
+        match &operation.parameters.as_ref().unwrap()[0] {
+            Referable::Inline(parameter) => assert_eq!(parameter.name, "petId"),
+            _ => panic!("Not matched"),
+        }
+        match operation.response(404).unwrap() {
+            Referable::Inline(response) => assert_eq!(response.description, "Entity not found"),
+            _ => panic!("Not matched"),
+        }
+        let segments: Vec<Segment> = SegmentIter::from((uri, operations, operation)).collect();
+        match segments[1] {
+            Segment::Number(_) => (),
+            _ => panic!("Not matched"),
+        }
+
+        let s = "
+        paths:
+          /pet:
+            get:
+              parameters:
+                - $ref: '#/parameters/limit'
+        ";
+        let mut root: Swagger = serde_yaml::from_str(s).unwrap();
+        let expected = ResolveError::Pointer(String::new(), "/parameters/limit".into());
+        assert_eq!(root.resolve_parameters(), Err(expected));
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        path.push("src/swagger/test.yaml");
+        let file = File::open(path.to_str().unwrap()).unwrap();
+        let document: serde_yaml::Value = serde_yaml::from_reader(&file).unwrap();
+        let root: Swagger = serde_yaml::from_value(document.clone()).unwrap();
+
+        // `required: false` is the default, and is not written back.
+        let mut expected = serde_json::to_value(&document).unwrap();
+        let paths = expected["paths"].as_object_mut().unwrap().values_mut();
+        for operation in paths.flat_map(|p| p.as_object_mut().unwrap().values_mut()) {
+            let parameters = operation["parameters"].as_array_mut().into_iter().flatten();
+            for parameter in parameters {
+                let parameter = parameter.as_object_mut().unwrap();
+                if parameter.get("required") == Some(&serde_json::Value::Bool(false)) {
+                    parameter.remove("required");
+                }
+            }
+        }
+        let yaml: serde_yaml::Value =
+            serde_yaml::from_str(&serde_yaml::to_string(&root).unwrap()).unwrap();
+        assert_eq!(serde_json::to_value(&yaml).unwrap(), expected);
+        let json: serde_json::Value =
+            serde_json::from_str(&serde_json::to_string(&root).unwrap()).unwrap();
+        assert_eq!(json, expected);
+
+        let s = "
+        swagger: '2.0'
+        definitions:
+          Size:
+            type: integer
+            enum:
+              - 1
+              - 2
+          Name:
+            type: string
+            minimum: 3
+            properties: {}
+        paths:
+          /pet:
+            get:
+              parameters:
+                - name: limit
+                  in: query
+                  type: integer
+        ";
+        let document: serde_yaml::Value = serde_yaml::from_str(s).unwrap();
+        let root: Swagger = serde_yaml::from_value(document.clone()).unwrap();
+        let yaml: serde_yaml::Value =
+            serde_yaml::from_str(&serde_yaml::to_string(&root).unwrap()).unwrap();
+        assert_eq!(yaml, document);
+    }
+
+    #[test]
+    fn test_extensions() {
+        let s = "
+        swagger: '2.0'
+        x-internal: true
+        definitions:
+          Pet:
+            type: object
+            x-go-name: PetModel
+            properties:
+              id:
+                type: integer
+                x-go-name: ID
+        paths:
+          /pet:
+            x-owner: pets
+            get:
+              x-rate-limit: 100
+              parameters:
+                - name: limit
+                  in: query
+                  type: integer
+                  x-internal: false
+              responses:
+                '200':
+                  description: Pets
+                x-cache: private
+        ";
+        let root: Swagger = serde_yaml::from_str(s).unwrap();
+        assert!(root
+            .extensions
+            .get_as::<bool>("x-internal")
+            .unwrap()
+            .unwrap());
+        assert!(root.extra.is_empty());
+
+        let pet = root.definitions.as_ref().unwrap().get("Pet").unwrap();
+        assert_eq!(
+            pet.extensions
+                .get_as::<String>("x-go-name")
+                .unwrap()
+                .unwrap(),
+            "PetModel"
+        );
+        assert!(pet.extra.is_empty());
+
+        let operations = root.paths.as_ref().unwrap().get("/pet").unwrap();
+        assert!(operations.extensions.get("x-owner").is_some());
+        let operation = operations.get.as_ref().unwrap();
+        assert_eq!(
+            operation
+                .extensions
+                .get_as::<u32>("x-rate-limit")
+                .unwrap()
+                .unwrap(),
+            100
+        );
+        let parameter = operation.inline_parameters().next().unwrap();
+        assert!(parameter.extensions().get("x-internal").is_some());
+        let responses = operation.responses.as_ref().unwrap();
+        assert!(responses.extensions.get("x-cache").is_some());
+        assert_eq!(responses.iter().count(), 1);
+
+        let reloaded: Swagger =
+            serde_yaml::from_str(&serde_yaml::to_string(&root).unwrap()).unwrap();
+        assert_eq!(reloaded, root);
     }
 }
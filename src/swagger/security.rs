@@ -0,0 +1,58 @@
+use std::collections::{BTreeMap, HashMap};
+
+use common::Extensions;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ApiKeyIn {
+    Header,
+    Query,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Flow {
+    Implicit,
+    Password,
+    Application,
+    AccessCode,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", tag = "type")]
+pub enum SecurityScheme {
+    Basic {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        description: Option<String>,
+        #[serde(flatten)]
+        extensions: Extensions,
+    },
+    ApiKey {
+        name: String,
+        #[serde(rename = "in")]
+        in_: ApiKeyIn,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        description: Option<String>,
+        #[serde(flatten)]
+        extensions: Extensions,
+    },
+    #[serde(rename = "oauth2")]
+    OAuth2 {
+        flow: Flow,
+        #[serde(rename = "authorizationUrl", skip_serializing_if = "Option::is_none")]
+        authorization_url: Option<String>,
+        #[serde(rename = "tokenUrl", skip_serializing_if = "Option::is_none")]
+        token_url: Option<String>,
+        scopes: BTreeMap<String, String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        description: Option<String>,
+        #[serde(flatten)]
+        extensions: Extensions,
+    },
+}
+
+pub type SecurityDefinitions = HashMap<String, SecurityScheme>;
+
+/// Names of security schemes that must all be satisfied, each with the
+/// scopes required of it.
+pub type SecurityRequirement = BTreeMap<String, Vec<String>>;
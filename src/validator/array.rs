@@ -9,7 +9,7 @@ pub struct ArrayValidator<'a> {
     validator: Box<dyn Validator<Yaml> + 'a>,
 }
 
-impl<'a, 'b> ArrayValidator<'a> {
+impl<'a> ArrayValidator<'a> {
     pub fn new(attr: &Attribute, querier: &'a dyn ValidatorQuerier) -> Self {
         ArrayValidator {
             validator: to_validator(attr, querier),
@@ -38,17 +38,16 @@ impl<'a> Validator<Yaml> for ArrayValidator<'a> {
 mod tests {
     extern crate yaml_rust;
 
-    use yaml_rust::YamlLoader;
+    use yaml_rust::{Yaml, YamlLoader};
 
     use super::{ArrayValidator, Validator};
     use common::Attribute;
-    use validator::object::ObjectValidator;
     use validator::reference::{Location, ValidatorQuerier};
 
     pub(crate) struct NoneQuerier;
 
     impl ValidatorQuerier for NoneQuerier {
-        fn get(&self, _: &Location) -> Option<ObjectValidator> {
+        fn get(&self, _: &Location) -> Option<Box<dyn Validator<Yaml> + '_>> {
             None
         }
     }
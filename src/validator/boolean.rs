@@ -8,7 +8,7 @@ impl Validator<Yaml> for BooleanValidator {
     fn validate(&self, yaml: &Yaml) -> Option<String> {
         match yaml {
             Yaml::Boolean(_) => None,
-            _ => return some_str!("field is not boolean"),
+            _ => some_str!("field is not boolean"),
         }
     }
 }
@@ -2,32 +2,88 @@ use yaml_rust::Yaml;
 
 use super::array::ArrayValidator;
 use super::boolean::BooleanValidator;
+use super::composition::{AllValidator, AnyOfValidator, NotValidator, OneOfValidator};
 use super::integer::IntegerValidator;
 use super::object::ObjectValidator;
-use super::reference::{Location, ReferenceValidator, ValidatorQuerier};
+use super::reference::{Location, ReferenceValidator, UnresolvedValidator, ValidatorQuerier};
 use super::string::StringValidator;
 use super::{UnknownValidator, Validator};
 use common::{Attribute, TypeDefinition};
 
+/// Accepts `null` and otherwise defers to the wrapped validator.
+struct NullableValidator<'a>(Box<dyn Validator<Yaml> + 'a>);
+
+impl<'a> Validator<Yaml> for NullableValidator<'a> {
+    fn validate(&self, yaml: &Yaml) -> Option<String> {
+        match yaml {
+            Yaml::Null => None,
+            _ => self.0.validate(yaml),
+        }
+    }
+}
+
+fn composition<'a>(
+    attribute: &Attribute, querier: &'a dyn ValidatorQuerier,
+) -> Vec<Box<dyn Validator<Yaml> + 'a>> {
+    let mut validators: Vec<Box<dyn Validator<Yaml> + 'a>> = Vec::new();
+    let malformed = |keyword: &str, e: serde_yaml::Error| -> Box<dyn Validator<Yaml> + 'a> {
+        let reason = format!("Malformed {}: {}", keyword, e);
+        Box::new(UnresolvedValidator { reason })
+    };
+    match attribute.one_of_branches() {
+        Some(Ok(branches)) => validators.push(Box::new(OneOfValidator::new(&branches, querier))),
+        Some(Err(e)) => validators.push(malformed("oneOf", e)),
+        None => (),
+    }
+    match attribute.any_of_branches() {
+        Some(Ok(branches)) => validators.push(Box::new(AnyOfValidator::new(&branches, querier))),
+        Some(Err(e)) => validators.push(malformed("anyOf", e)),
+        None => (),
+    }
+    match attribute.not_branch() {
+        Some(Ok(branch)) => validators.push(Box::new(NotValidator::new(&branch, querier))),
+        Some(Err(e)) => validators.push(malformed("not", e)),
+        None => (),
+    }
+    validators
+}
+
 pub(crate) fn to_validator<'a>(
     attribute: &Attribute, querier: &'a dyn ValidatorQuerier,
 ) -> Box<dyn Validator<Yaml> + 'a> {
-    if attribute.reference.is_none() {
-        match attribute.definition.as_ref().unwrap() {
-            TypeDefinition::Boolean => Box::new(BooleanValidator {}),
-            TypeDefinition::Integer(integer_type) => Box::new(IntegerValidator::from(integer_type)),
-            TypeDefinition::String(string_type) => Box::new(StringValidator::from(string_type)),
-            TypeDefinition::Array { items } => Box::new(ArrayValidator::new(items, querier)),
-            TypeDefinition::Object(object_type) => {
-                Box::new(ObjectValidator::new(object_type, querier))
+    let mut validators = composition(attribute, querier);
+    let typed: Option<Box<dyn Validator<Yaml> + 'a>> = match &attribute.reference {
+        None => match &attribute.definition {
+            Some(TypeDefinition::Boolean) => Some(Box::new(BooleanValidator {})),
+            Some(TypeDefinition::Integer(integer_type)) => {
+                Some(Box::new(IntegerValidator::from(integer_type)))
             }
-            _ => Box::new(UnknownValidator {}),
-        }
-    } else {
-        let ref_name = attribute.reference.as_ref().unwrap();
-        Box::new(ReferenceValidator {
-            location: Location::from(ref_name.to_string()),
+            Some(TypeDefinition::String(string_type)) => {
+                Some(Box::new(StringValidator::from(string_type)))
+            }
+            Some(TypeDefinition::Array { items }) => {
+                Some(Box::new(ArrayValidator::new(items, querier)))
+            }
+            Some(TypeDefinition::Object(object_type)) => {
+                Some(Box::new(ObjectValidator::new(object_type, querier)))
+            }
+            // A schema made only of composition keywords has no type of
+            // its own to check.
+            None if !validators.is_empty() => None,
+            _ => Some(Box::new(UnknownValidator {})),
+        },
+        Some(ref_name) => Some(Box::new(ReferenceValidator::new(
+            Location::from(ref_name.to_string()),
             querier,
-        })
+        ))),
+    };
+    validators.splice(0..0, typed);
+    let validator = match validators.len() {
+        1 => validators.pop().unwrap(),
+        _ => Box::new(AllValidator(validators)),
+    };
+    match attribute.nullable {
+        true => Box::new(NullableValidator(validator)),
+        false => validator,
     }
 }
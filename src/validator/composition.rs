@@ -0,0 +1,184 @@
+use yaml_rust::Yaml;
+
+use super::common::to_validator;
+use super::reference::ValidatorQuerier;
+use super::Validator;
+use common::Attribute;
+
+/// Validates against every validator, reporting the first failure.
+pub(crate) struct AllValidator<'a>(pub(crate) Vec<Box<dyn Validator<Yaml> + 'a>>);
+
+impl<'a> Validator<Yaml> for AllValidator<'a> {
+    fn validate(&self, yaml: &Yaml) -> Option<String> {
+        self.0.iter().find_map(|validator| validator.validate(yaml))
+    }
+}
+
+fn to_validators<'a>(
+    branches: &[Attribute], querier: &'a dyn ValidatorQuerier,
+) -> Vec<Box<dyn Validator<Yaml> + 'a>> {
+    branches.iter().map(|b| to_validator(b, querier)).collect()
+}
+
+/// Runs every branch, returning the error of each one that failed along
+/// with the indexes of those that matched.
+fn run(branches: &[Box<dyn Validator<Yaml> + '_>], yaml: &Yaml) -> (Vec<String>, Vec<usize>) {
+    let mut errors = Vec::new();
+    let mut matched = Vec::new();
+    for (index, branch) in branches.iter().enumerate() {
+        match branch.validate(yaml) {
+            Some(error) => errors.push(format!("#{}: {}", index, error)),
+            None => matched.push(index),
+        }
+    }
+    (errors, matched)
+}
+
+pub struct OneOfValidator<'a> {
+    branches: Vec<Box<dyn Validator<Yaml> + 'a>>,
+}
+
+impl<'a> OneOfValidator<'a> {
+    pub fn new(branches: &[Attribute], querier: &'a dyn ValidatorQuerier) -> Self {
+        OneOfValidator {
+            branches: to_validators(branches, querier),
+        }
+    }
+}
+
+impl<'a> Validator<Yaml> for OneOfValidator<'a> {
+    fn validate(&self, yaml: &Yaml) -> Option<String> {
+        let (errors, matched) = run(&self.branches, yaml);
+        match matched.len() {
+            1 => None,
+            0 => some_str!("No branch of oneOf matched: {}", errors.join("; ")),
+            _ => {
+                let matched: Vec<String> = matched.iter().map(|i| format!("#{}", i)).collect();
+                some_str!(
+                    "More than one branch of oneOf matched: {}",
+                    matched.join(", ")
+                )
+            }
+        }
+    }
+}
+
+pub struct AnyOfValidator<'a> {
+    branches: Vec<Box<dyn Validator<Yaml> + 'a>>,
+}
+
+impl<'a> AnyOfValidator<'a> {
+    pub fn new(branches: &[Attribute], querier: &'a dyn ValidatorQuerier) -> Self {
+        AnyOfValidator {
+            branches: to_validators(branches, querier),
+        }
+    }
+}
+
+impl<'a> Validator<Yaml> for AnyOfValidator<'a> {
+    fn validate(&self, yaml: &Yaml) -> Option<String> {
+        let (errors, matched) = run(&self.branches, yaml);
+        match matched.is_empty() {
+            true => some_str!("No branch of anyOf matched: {}", errors.join("; ")),
+            false => None,
+        }
+    }
+}
+
+pub struct NotValidator<'a> {
+    validator: Box<dyn Validator<Yaml> + 'a>,
+}
+
+impl<'a> NotValidator<'a> {
+    pub fn new(attr: &Attribute, querier: &'a dyn ValidatorQuerier) -> Self {
+        NotValidator {
+            validator: to_validator(attr, querier),
+        }
+    }
+}
+
+impl<'a> Validator<Yaml> for NotValidator<'a> {
+    fn validate(&self, yaml: &Yaml) -> Option<String> {
+        match self.validator.validate(yaml) {
+            Some(_) => None,
+            None => some_str!("field matches the schema of not"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate yaml_rust;
+
+    use std::collections::HashMap;
+
+    use yaml_rust::YamlLoader;
+
+    use super::Validator;
+    use common::Attribute;
+    use validator::reference::{Location, ReferenceValidator};
+
+    fn load(s: &str) -> yaml_rust::Yaml {
+        YamlLoader::load_from_str(s).unwrap().remove(0)
+    }
+
+    #[test]
+    fn test_one_of() {
+        let schema = "
+        Id:
+          oneOf:
+            - type: integer
+            - type: string
+              enum:
+                - auto
+            - type: integer
+              maximum: 10
+        ";
+        let attributes: HashMap<String, Attribute> = serde_yaml::from_str(schema).unwrap();
+        let location = Location::from("#/definitions/Id".to_string());
+        let v = ReferenceValidator::new(location, &attributes);
+
+        assert_eq!(v.validate(&load("auto")), None);
+        assert_eq!(v.validate(&load("42")), None);
+        assert_eq!(
+            v.validate(&load("5")),
+            some_str!("More than one branch of oneOf matched: #0, #2")
+        );
+        let error = v.validate(&load("true")).unwrap();
+        assert!(error.starts_with("No branch of oneOf matched: #0: field is not integer; #1: "));
+    }
+
+    #[test]
+    fn test_any_of_and_not() {
+        let schema = "
+        Pet:
+          type: object
+          properties:
+            name:
+              type: string
+              x-anyOf:
+                - type: string
+                  enum:
+                    - cat
+                - type: string
+                  enum:
+                    - dog
+            id:
+              type: integer
+              not:
+                type: integer
+                maximum: 0
+        ";
+        let attributes: HashMap<String, Attribute> = serde_yaml::from_str(schema).unwrap();
+        let location = Location::from("#/definitions/Pet".to_string());
+        let v = ReferenceValidator::new(location, &attributes);
+
+        assert_eq!(v.validate(&load("{name: dog, id: 1}")), None);
+        let error = v.validate(&load("{name: fish}")).unwrap();
+        assert!(error.starts_with("No branch of anyOf matched: #0: "));
+        assert_eq!(
+            v.validate(&load("{id: -1}")),
+            some_str!("field matches the schema of not")
+        );
+    }
+}
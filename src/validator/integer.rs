@@ -1,22 +1,50 @@
 use std::collections::HashMap;
 use std::sync::atomic::{AtomicPtr, Ordering};
 
+use derive_more::Display;
 use yaml_rust::Yaml;
 
 use super::format::{FormatValidator, NO_FORMAT, UNKNOWN_FORMAT};
 use super::Validator;
 use common::IntegerType;
 
+#[derive(Clone, Display)]
+#[display(fmt = "int32")]
+struct Int32Format;
+
+impl FormatValidator<i64> for Int32Format {
+    fn validate(&self, value: i64) -> bool {
+        i64::from(i32::MIN) <= value && value <= i64::from(i32::MAX)
+    }
+}
+
+#[derive(Clone, Display)]
+#[display(fmt = "int64")]
+struct Int64Format;
+
+impl FormatValidator<i64> for Int64Format {
+    fn validate(&self, _: i64) -> bool {
+        true
+    }
+}
+
+const INT32_FORMAT: &dyn FormatValidator<i64> = &Int32Format {};
+const INT64_FORMAT: &dyn FormatValidator<i64> = &Int64Format {};
+
+pub const INTEGER_FORMATS: [(&str, &dyn FormatValidator<i64>); 2] =
+    [("int32", INT32_FORMAT), ("int64", INT64_FORMAT)];
+
 type Formats = HashMap<&'static str, &'static dyn FormatValidator<i64>>;
 
 lazy_static! {
-    pub static ref FORMATS: AtomicPtr<Formats> =
-        AtomicPtr::new(&mut HashMap::new() as *mut Formats);
+    pub static ref FORMATS: AtomicPtr<Formats> = AtomicPtr::new(Box::into_raw(Box::new(
+        INTEGER_FORMATS.iter().cloned().collect()
+    )));
 }
 
 pub fn set_formats(formats: &Formats) {
-    let mut new_formats: Formats = formats.clone();
-    FORMATS.store(&mut new_formats, Ordering::Relaxed)
+    let new_formats = Box::new(formats.clone());
+    FORMATS.store(Box::into_raw(new_formats), Ordering::Relaxed)
 }
 
 #[derive(Clone)]
@@ -24,9 +52,10 @@ pub struct IntegerValidator {
     format: &'static dyn FormatValidator<i64>,
     pub minimum: i64,
     pub maximum: i64,
+    pub choices: Vec<i64>,
 }
 
-impl<'a> From<&IntegerType> for IntegerValidator {
+impl From<&IntegerType> for IntegerValidator {
     fn from(type_integer: &IntegerType) -> Self {
         let formats = unsafe { &*FORMATS.load(Ordering::Relaxed) };
         let format = match &type_integer.format {
@@ -38,8 +67,9 @@ impl<'a> From<&IntegerType> for IntegerValidator {
         };
         IntegerValidator {
             format,
-            minimum: type_integer.minimum.unwrap_or(std::i64::MIN),
-            maximum: type_integer.maximum.unwrap_or(std::i64::MAX),
+            minimum: type_integer.minimum.unwrap_or(i64::MIN),
+            maximum: type_integer.maximum.unwrap_or(i64::MAX),
+            choices: type_integer.choices.clone().unwrap_or_default(),
         }
     }
 }
@@ -55,6 +85,10 @@ impl Validator<i64> for IntegerValidator {
         if !self.format.validate(*value) {
             return some_str!("field is not format of {}", self.format);
         }
+        if !self.choices.is_empty() && !self.choices.contains(value) {
+            let choices: Vec<String> = self.choices.iter().map(|c| c.to_string()).collect();
+            return some_str!("field is not one of [{}]", choices.join(", "));
+        }
         None
     }
 }
@@ -63,7 +97,7 @@ impl Validator<Yaml> for IntegerValidator {
     fn validate(&self, yaml: &Yaml) -> Option<String> {
         match yaml {
             Yaml::Integer(i) => self.validate(i),
-            _ => return some_str!("field is not integer"),
+            _ => some_str!("field is not integer"),
         }
     }
 }
@@ -72,8 +106,9 @@ impl Default for IntegerValidator {
     fn default() -> IntegerValidator {
         IntegerValidator {
             format: &NO_FORMAT,
-            minimum: std::i64::MIN,
-            maximum: std::i64::MAX,
+            minimum: i64::MIN,
+            maximum: i64::MAX,
+            choices: Vec::new(),
         }
     }
 }
@@ -107,5 +142,21 @@ mod tests {
         };
         assert_eq!(v.validate(&doc["b"]), some_str!("field is too large"));
         assert_eq!(v.validate(&doc["c"]), some_str!("field is not integer"));
+
+        let integer_type = serde_yaml::from_str("format: int32").unwrap();
+        let v = IntegerValidator::from(&integer_type);
+        assert_eq!(v.validate(&doc["b"]), None);
+        assert_eq!(
+            v.validate(&(1i64 << 40)),
+            some_str!("field is not format of int32")
+        );
+
+        let integer_type = serde_yaml::from_str("enum: [1, 2]").unwrap();
+        let v = IntegerValidator::from(&integer_type);
+        assert_eq!(v.validate(&doc["a"]), None);
+        assert_eq!(
+            v.validate(&doc["b"]),
+            some_str!("field is not one of [1, 2]")
+        );
     }
 }
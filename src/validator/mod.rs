@@ -15,15 +15,16 @@ pub struct UnknownValidator;
 
 impl<T> Validator<T> for UnknownValidator {
     fn validate(&self, _: &T) -> Option<String> {
-        return some_str!("Unknown type");
+        some_str!("Unknown type")
     }
 }
 
-mod array;
+pub mod array;
 pub mod boolean;
-mod common;
+pub(crate) mod common;
+pub mod composition;
 pub mod format;
 pub mod integer;
-mod object;
-mod reference;
+pub mod object;
+pub mod reference;
 pub mod string;
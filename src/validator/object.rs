@@ -13,7 +13,7 @@ pub struct ObjectValidator<'a> {
     required: Vec<String>,
 }
 
-impl<'a, 'b> ObjectValidator<'a> {
+impl<'a> ObjectValidator<'a> {
     pub fn new(obj_type: &ObjectType, querier: &'a dyn ValidatorQuerier) -> Self {
         let cap = obj_type.properties.len();
         let mut properties: HashMap<String, Box<dyn Validator<Yaml> + 'a>> =
@@ -66,10 +66,11 @@ impl<'a> Validator<Yaml> for ObjectValidator<'a> {
 }
 
 #[cfg(test)]
+#[allow(clippy::needless_borrow)]
 mod tests {
     extern crate yaml_rust;
 
-    use yaml_rust::YamlLoader;
+    use yaml_rust::{Yaml, YamlLoader};
 
     use super::{ObjectValidator, Validator};
     use common::{Attribute, TypeDefinition};
@@ -78,7 +79,7 @@ mod tests {
     pub(crate) struct NoneQuerier;
 
     impl ValidatorQuerier for NoneQuerier {
-        fn get(&self, _: &Location) -> Option<ObjectValidator> {
+        fn get(&self, _: &Location) -> Option<Box<dyn Validator<Yaml> + '_>> {
             None
         }
     }
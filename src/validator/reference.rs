@@ -1,84 +1,181 @@
-use std::collections::HashMap;
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
 
+use percent_encoding::{percent_decode_str, percent_encode, AsciiSet, CONTROLS};
 use yaml_rust::Yaml;
 
-use super::object::ObjectValidator;
+use super::common::to_validator;
 use super::Validator;
-use common::{Attribute, TypeDefinition};
+use common::Attribute;
 
 #[derive(Clone, PartialEq, Debug)]
 pub enum Location {
     Local(String),
+    /// A schema under `components/schemas` of an OpenAPI 3.0 document.
+    Component(String),
+    Parameter(String),
+    Response(String),
+    /// A file path, relative to the referring document unless absolute, and
+    /// a JSON pointer into that file which is empty when the whole file is
+    /// referenced.
+    Remote(PathBuf, String),
     Unknown,
 }
 
 impl From<String> for Location {
     fn from(uri: String) -> Location {
-        if !uri.contains('#') {
-            return Location::Unknown;
-        }
         let splitted: Vec<&str> = uri.splitn(2, '#').collect();
-        if splitted.len() != 2 || splitted[0].len() > 0 {
-            return Location::Unknown;
+        let pointer = splitted.get(1).cloned().unwrap_or("");
+        if !splitted[0].is_empty() {
+            return Location::Remote(decode_path(splitted[0]), pointer.to_string());
+        }
+        if let Some(name) = pointer.strip_prefix("/definitions/") {
+            return Location::Local(name.to_string());
+        }
+        if let Some(name) = pointer.strip_prefix("/components/schemas/") {
+            return Location::Component(name.to_string());
+        }
+        if let Some(name) = pointer.strip_prefix("/parameters/") {
+            return Location::Parameter(name.to_string());
         }
-        if !splitted[1].contains("/definitions/") {
-            return Location::Unknown;
+        if let Some(name) = pointer.strip_prefix("/responses/") {
+            return Location::Response(name.to_string());
         }
-        Location::Local(splitted[1].replace("/definitions/", ""))
+        Location::Unknown
+    }
+}
+
+/// Characters escaped when writing a path into a reference: those a URI
+/// cannot hold and `#`, which would end the path early.
+const PATH: &AsciiSet = &CONTROLS.add(b' ').add(b'"').add(b'#').add(b'%');
+
+#[cfg(unix)]
+fn path_bytes(path: &Path) -> Cow<'_, [u8]> {
+    use std::os::unix::ffi::OsStrExt;
+    Cow::Borrowed(path.as_os_str().as_bytes())
+}
+
+#[cfg(not(unix))]
+fn path_bytes(path: &Path) -> Cow<'_, [u8]> {
+    match path.to_string_lossy() {
+        Cow::Borrowed(text) => Cow::Borrowed(text.as_bytes()),
+        Cow::Owned(text) => Cow::Owned(text.into_bytes()),
+    }
+}
+
+#[cfg(unix)]
+fn bytes_path(bytes: Vec<u8>) -> PathBuf {
+    use std::ffi::OsString;
+    use std::os::unix::ffi::OsStringExt;
+    PathBuf::from(OsString::from_vec(bytes))
+}
+
+#[cfg(not(unix))]
+fn bytes_path(bytes: Vec<u8>) -> PathBuf {
+    PathBuf::from(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+/// Reads the file part of a reference, either relative or a `file://` URI.
+fn decode_path(file: &str) -> PathBuf {
+    let file = file.strip_prefix("file://").unwrap_or(file);
+    bytes_path(percent_decode_str(file).collect())
+}
+
+/// Writes a reference to `pointer` within the file at `path`, which
+/// `Location::from` reads back as the same `Location::Remote`.
+pub fn file_reference(path: &Path, pointer: &str) -> String {
+    let file: String = percent_encode(&path_bytes(path), PATH).collect();
+    match path.is_absolute() {
+        true => format!("file://{}#{}", file, pointer),
+        false => format!("{}#{}", file, pointer),
     }
 }
 
 pub trait ValidatorQuerier {
-    fn get(&self, location: &Location) -> Option<ObjectValidator>;
-}
-
-impl<'a> ValidatorQuerier for HashMap<String, Attribute> {
-    fn get(&self, location: &Location) -> Option<ObjectValidator> {
-        match location {
-            Location::Local(path) => {
-                if !self.contains_key(path) {
-                    return None;
-                }
-                let attr = self.get(path).unwrap();
-                if !attr.definition.is_some() {
-                    return None;
-                }
-                match attr.definition.as_ref().unwrap() {
-                    TypeDefinition::Object(object) => {
-                        let querier: &dyn ValidatorQuerier = self;
-                        Some(ObjectValidator::new(&object, querier))
-                    }
-                    _ => None,
-                }
+    fn get(&self, location: &Location) -> Option<Box<dyn Validator<Yaml> + '_>>;
+}
+
+/// Stands in for a reference that cannot be resolved, reporting why.
+pub(crate) struct UnresolvedValidator {
+    pub(crate) reason: String,
+}
+
+impl Validator<Yaml> for UnresolvedValidator {
+    fn validate(&self, _: &Yaml) -> Option<String> {
+        Some(self.reason.clone())
+    }
+}
+
+impl ValidatorQuerier for HashMap<String, Attribute> {
+    fn get(&self, location: &Location) -> Option<Box<dyn Validator<Yaml> + '_>> {
+        // Follow reference chains here rather than through nested
+        // ReferenceValidators, so that an alias cycle is reported instead of
+        // recursing forever on validation.
+        let mut visited: HashSet<&str> = HashSet::new();
+        let mut location = location.clone();
+        loop {
+            let (path, attr) = match location {
+                Location::Local(path) | Location::Component(path) => self.get_key_value(&path)?,
+                _ => return None,
+            };
+            if !visited.insert(path) {
+                let reason = "Circular reference".to_string();
+                return Some(Box::new(UnresolvedValidator { reason }));
+            }
+            match &attr.reference {
+                Some(reference) => location = Location::from(reference.to_string()),
+                None => return Some(to_validator(attr, self)),
             }
-            _ => None,
         }
     }
 }
 
+/// Validates against a referenced definition, resolving it on first use.
+///
+/// Resolution is deferred so recursive definitions never expand eagerly: each
+/// level of a cycle is only built when a document actually nests that deep,
+/// and is kept afterwards so repeated validation does not rebuild it.
 pub(crate) struct ReferenceValidator<'a> {
     pub(crate) location: Location,
     pub(crate) querier: &'a dyn ValidatorQuerier,
+    resolved: RefCell<Option<Box<dyn Validator<Yaml> + 'a>>>,
+}
+
+impl<'a> ReferenceValidator<'a> {
+    pub(crate) fn new(location: Location, querier: &'a dyn ValidatorQuerier) -> Self {
+        ReferenceValidator {
+            location,
+            querier,
+            resolved: RefCell::new(None),
+        }
+    }
 }
 
 impl<'a> Validator<Yaml> for ReferenceValidator<'a> {
     fn validate(&self, yaml: &Yaml) -> Option<String> {
-        match self.querier.get(&self.location) {
-            Some(v) => v.validate(yaml),
-            None => some_str!("No such reference"),
+        if self.resolved.borrow().is_none() {
+            match self.querier.get(&self.location) {
+                Some(v) => *self.resolved.borrow_mut() = Some(v),
+                None => return some_str!("No such reference"),
+            }
         }
+        self.resolved.borrow().as_ref().unwrap().validate(yaml)
     }
 }
 
 #[cfg(test)]
+#[allow(clippy::needless_borrow)]
 mod tests {
     extern crate yaml_rust;
 
     use std::collections::HashMap;
+    use std::path::PathBuf;
 
     use yaml_rust::YamlLoader;
 
-    use super::{Location, ReferenceValidator, Validator};
+    use super::{file_reference, Location, ReferenceValidator, Validator};
     use common::Attribute;
 
     #[test]
@@ -99,10 +196,8 @@ mod tests {
         ";
         let attributes: HashMap<String, Attribute> = serde_yaml::from_str(&schema).unwrap();
 
-        let v = ReferenceValidator {
-            location: Location::from("#/definitions/Test".to_string()),
-            querier: &attributes,
-        };
+        let location = Location::from("#/definitions/Test".to_string());
+        let v = ReferenceValidator::new(location, &attributes);
 
         let s = "
         id: 1
@@ -113,4 +208,103 @@ mod tests {
         let doc = &docs[0];
         assert_eq!(v.validate(&doc), None);
     }
+
+    #[test]
+    fn test_recursive_reference() {
+        let schema = "
+        TreeNode:
+          type: object
+          properties:
+            name:
+              type: string
+            children:
+              type: array
+              items:
+                $ref: '#/definitions/TreeNode'
+        ";
+        let attributes: HashMap<String, Attribute> = serde_yaml::from_str(schema).unwrap();
+
+        let location = Location::from("#/definitions/TreeNode".to_string());
+        let v = ReferenceValidator::new(location, &attributes);
+
+        let s = "
+        name: root
+        children:
+          - name: a
+            children:
+              - name: b
+          - name: c
+        ";
+        let docs = YamlLoader::load_from_str(s).unwrap();
+        let doc = &docs[0];
+        assert_eq!(v.validate(doc), None);
+        assert_eq!(v.validate(doc), None);
+
+        let docs = YamlLoader::load_from_str("children: [{children: [{name: 1}]}]").unwrap();
+        let doc = &docs[0];
+        assert_eq!(v.validate(doc), some_str!("field is not string"));
+    }
+
+    #[test]
+    fn test_non_object_reference() {
+        let schema = "
+        Status:
+          type: string
+          enum:
+            - available
+            - sold
+        Alias:
+          $ref: '#/definitions/Status'
+        Ping:
+          $ref: '#/definitions/Pong'
+        Pong:
+          $ref: '#/definitions/Ping'
+        ";
+        let attributes: HashMap<String, Attribute> = serde_yaml::from_str(schema).unwrap();
+
+        let docs = YamlLoader::load_from_str("[sold, 1]").unwrap();
+        let doc = &docs[0];
+
+        let location = Location::from("#/definitions/Alias".to_string());
+        let v = ReferenceValidator::new(location, &attributes);
+        assert_eq!(v.validate(&doc[0]), None);
+        assert_eq!(v.validate(&doc[1]), some_str!("field is not string"));
+
+        let location = Location::from("#/definitions/Ping".to_string());
+        let v = ReferenceValidator::new(location, &attributes);
+        assert_eq!(v.validate(&doc[0]), some_str!("Circular reference"));
+
+        let location = Location::from("#/definitions/Missing".to_string());
+        let v = ReferenceValidator::new(location, &attributes);
+        assert_eq!(v.validate(&doc[0]), some_str!("No such reference"));
+    }
+
+    #[test]
+    fn test_location() {
+        let location = Location::from("#/definitions/Pet".to_string());
+        assert_eq!(location, Location::Local("Pet".into()));
+        let location = Location::from("common.yaml#/definitions/Error".to_string());
+        assert_eq!(
+            location,
+            Location::Remote("common.yaml".into(), "/definitions/Error".into())
+        );
+        let location = Location::from("pet.yaml".to_string());
+        assert_eq!(location, Location::Remote("pet.yaml".into(), "".into()));
+        let path = PathBuf::from("/specs/a#b 100%.yaml");
+        let reference = file_reference(&path, "/definitions/Error");
+        assert_eq!(
+            reference,
+            "file:///specs/a%23b%20100%25.yaml#/definitions/Error"
+        );
+        let location = Location::from(reference);
+        assert_eq!(
+            location,
+            Location::Remote(path, "/definitions/Error".into())
+        );
+        let location = Location::from("#/parameters/limit".to_string());
+        assert_eq!(location, Location::Parameter("limit".into()));
+        let location = Location::from("#/responses/NotFound".to_string());
+        assert_eq!(location, Location::Response("NotFound".into()));
+        assert_eq!(Location::from("#/info".to_string()), Location::Unknown);
+    }
 }
@@ -28,13 +28,14 @@ pub const STRING_FORMATS: [(&str, &dyn FormatValidator<String>); 1] = [("ipv4",
 type Formats = HashMap<&'static str, &'static dyn FormatValidator<String>>;
 
 lazy_static! {
-    pub static ref FORMATS: AtomicPtr<Formats> =
-        AtomicPtr::new(&mut STRING_FORMATS.iter().cloned().collect() as *mut Formats);
+    pub static ref FORMATS: AtomicPtr<Formats> = AtomicPtr::new(Box::into_raw(Box::new(
+        STRING_FORMATS.iter().cloned().collect()
+    )));
 }
 
 pub fn set_formats(formats: &Formats) {
-    let mut new_formats: Formats = formats.clone();
-    FORMATS.store(&mut new_formats, Ordering::Relaxed)
+    let new_formats = Box::new(formats.clone());
+    FORMATS.store(Box::into_raw(new_formats), Ordering::Relaxed)
 }
 
 #[derive(Clone)]
@@ -43,10 +44,10 @@ pub struct StringValidator {
     pub choices: HashSet<String>,
 }
 
-impl<'a> From<&'a StringType> for StringValidator {
+impl From<&StringType> for StringValidator {
     fn from(type_string: &StringType) -> StringValidator {
         let formats = unsafe { &*FORMATS.load(Ordering::Relaxed) };
-        let format = match &type_string.format.as_ref() {
+        let format = match type_string.format.as_ref() {
             Some(name) => match formats.get(name.as_str()) {
                 Some(&v) => v,
                 None => &UNKNOWN_FORMAT,
@@ -56,7 +57,7 @@ impl<'a> From<&'a StringType> for StringValidator {
         StringValidator {
             format,
             choices: match &type_string.choices {
-                Some(choices) => choices.clone(),
+                Some(choices) => choices.iter().cloned().collect(),
                 None => HashSet::default(),
             },
         }
@@ -66,14 +67,14 @@ impl<'a> From<&'a StringType> for StringValidator {
 impl StringValidator {
     fn format_choices(&self) -> String {
         let mut s = String::new();
-        s.push_str("[");
+        s.push('[');
         for choice in self.choices.iter() {
             s.push_str(choice);
             s.push_str(", ")
         }
         s.truncate(s.len() - 2);
-        s.push_str("]");
-        s.into()
+        s.push(']');
+        s
     }
 }
 
@@ -82,7 +83,7 @@ impl Validator<str> for StringValidator {
         if !self.format.validate(s.to_string()) {
             return some_str!("field is not format of {}", self.format);
         }
-        if self.choices.len() > 0 && !self.choices.contains(s) {
+        if !self.choices.is_empty() && !self.choices.contains(s) {
             return some_str!("field is not one of {}", self.format_choices());
         }
         None
@@ -92,8 +93,8 @@ impl Validator<str> for StringValidator {
 impl Validator<Yaml> for StringValidator {
     fn validate(&self, yaml: &Yaml) -> Option<String> {
         match yaml {
-            Yaml::String(s) => self.validate(&s as &str),
-            _ => return some_str!("field is not string"),
+            Yaml::String(s) => self.validate(s as &str),
+            _ => some_str!("field is not string"),
         }
     }
 }
@@ -108,6 +109,7 @@ impl Default for StringValidator {
 }
 
 #[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
 mod tests {
     extern crate yaml_rust;
 